@@ -0,0 +1,289 @@
+//! A fixed size set of bits that can be changed from many threads at once
+//!
+//! Every bit operation is a single atomic instruction on the `AtomicU64` word holding the bit, so
+//! no locks are taken. Each operation takes the memory ordering to use, just like the methods of
+//! `AtomicU64` do. Operations that look at more than one word, such as `count_ones` and `snapshot`,
+//! read the words one at a time, so they may see some changes made while they run and miss others.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use words::{words_for_bits, WORD_BITS};
+use {BitInformation, BitIteration, BitSet};
+
+/// A fixed number of bits backed by `AtomicU64` words
+///
+/// Methods that only read, such as `get`, `count_ones` and `snapshot`, panic if given
+/// `Ordering::Release` or `Ordering::AcqRel`, the same way `AtomicU64::load` does. Methods that
+/// change bits accept every ordering.
+pub struct AtomicBitSet {
+    words: Box<[AtomicU64]>,
+    len: usize,
+}
+
+impl AtomicBitSet {
+
+    /// Creates an AtomicBitSet of `len` clear bits
+    pub fn new(len: usize) -> AtomicBitSet {
+        let words: Vec<AtomicU64> = (0..words_for_bits(len)).map(|_| AtomicU64::new(0)).collect();
+        AtomicBitSet { words: words.into_boxed_slice(), len }
+    }
+
+    /// Creates an AtomicBitSet holding the members of a BitSet below `len`
+    pub fn from_bit_set(set: &BitSet, len: usize) -> AtomicBitSet {
+        let bits = AtomicBitSet::new(len);
+        for member in set.range(..len) {
+            bits.set(member, Ordering::Relaxed);
+        }
+        bits
+    }
+
+    /// Gets the number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if there are no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks to see if bit X is set, panicking if it is out of range
+    pub fn get(&self, index: usize, order: Ordering) -> bool {
+        let (word, mask) = self.locate(index);
+        word.load(order) & mask != 0
+    }
+
+    /// Sets bit X, panicking if it is out of range
+    pub fn set(&self, index: usize, order: Ordering) {
+        self.test_and_set(index, order);
+    }
+
+    /// Clears bit X, panicking if it is out of range
+    pub fn clear(&self, index: usize, order: Ordering) {
+        self.test_and_clear(index, order);
+    }
+
+    /// Sets bit X and returns whether it was already set, panicking if it is out of range
+    pub fn test_and_set(&self, index: usize, order: Ordering) -> bool {
+        let (word, mask) = self.locate(index);
+        word.fetch_or(mask, order) & mask != 0
+    }
+
+    /// Clears bit X and returns whether it was set, panicking if it is out of range
+    pub fn test_and_clear(&self, index: usize, order: Ordering) -> bool {
+        let (word, mask) = self.locate(index);
+        word.fetch_and(!mask, order) & mask != 0
+    }
+
+    /// Flips bit X and returns whether it was set, panicking if it is out of range
+    pub fn fetch_toggle(&self, index: usize, order: Ordering) -> bool {
+        let (word, mask) = self.locate(index);
+        word.fetch_xor(mask, order) & mask != 0
+    }
+
+    /// Finds the first clear bit and sets it, returning its position, or None if every bit is set
+    ///
+    /// When several threads claim at once, each of them gets a different bit.
+    pub fn claim_first_zero(&self, order: Ordering) -> Option<usize> {
+        for (index, word) in self.words.iter().enumerate() {
+            let valid = self.valid_bits(index);
+            let mut current = word.load(Ordering::Relaxed);
+
+            loop {
+                let free = !current & valid;
+                if free == 0 {
+                    break;
+                }
+
+                let mask = 1 << free.trailing_zeros();
+                let previous = word.fetch_or(mask, order);
+                if previous & mask == 0 {
+                    return Some(index * WORD_BITS + free.trailing_zeros() as usize);
+                }
+                current = previous;
+            }
+        }
+
+        None
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self, order: Ordering) -> usize {
+        self.words.iter().map(|word| word.load(order).count_ones() as usize).sum()
+    }
+
+    /// Clears every bit
+    ///
+    /// Each word is cleared with a swap rather than a store, so any ordering can be used here.
+    pub fn clear_all(&self, order: Ordering) {
+        for word in self.words.iter() {
+            word.swap(0, order);
+        }
+    }
+
+    /// Copies the set bits into a BitSet
+    pub fn snapshot(&self, order: Ordering) -> BitSet {
+        let words: Vec<u64> = self.words.iter().map(|word| word.load(order)).collect();
+        BitSet::from_words(&words)
+    }
+
+    /// Gets the word holding bit X and the mask of that bit within it
+    fn locate(&self, index: usize) -> (&AtomicU64, u64) {
+        assert!(index < self.len, "bit index {} is out of range for length {}", index, self.len);
+        (&self.words[index / WORD_BITS], 1 << (index % WORD_BITS))
+    }
+
+    /// Gets a mask of the bits of word X that are within the length
+    fn valid_bits(&self, index: usize) -> u64 {
+        let remaining = self.len - index * WORD_BITS;
+        if remaining >= WORD_BITS {
+            u64::MAX
+        } else {
+            (1 << remaining) - 1
+        }
+    }
+
+}
+
+impl BitInformation for AtomicBitSet {
+
+    fn number_of_bits(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if bit X is set with a `SeqCst` load, returning false if X is out of bounds
+    fn has_x_bit(&self, position: usize) -> bool {
+        position < self.len && self.get(position, Ordering::SeqCst)
+    }
+
+}
+
+impl BitIteration for AtomicBitSet {}
+
+impl fmt::Debug for AtomicBitSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.snapshot(Ordering::SeqCst).iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_set_and_clear() {
+        let bits = AtomicBitSet::new(100);
+        assert_eq!(100, bits.len());
+
+        bits.set(3, Ordering::Relaxed);
+        bits.set(99, Ordering::Relaxed);
+        assert!(bits.get(3, Ordering::Relaxed));
+        assert!(bits.get(99, Ordering::Relaxed));
+        assert!(!bits.get(4, Ordering::Relaxed));
+
+        bits.clear(3, Ordering::Relaxed);
+        assert!(!bits.get(3, Ordering::Relaxed));
+        assert_eq!(1, bits.count_ones(Ordering::Relaxed));
+
+        bits.clear_all(Ordering::Relaxed);
+        assert_eq!(0, bits.count_ones(Ordering::Relaxed));
+
+        bits.set(10, Ordering::Relaxed);
+        bits.clear_all(Ordering::Acquire);
+        bits.clear_all(Ordering::AcqRel);
+        assert_eq!(0, bits.count_ones(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_fetch_operations() {
+        let bits = AtomicBitSet::new(70);
+
+        assert!(!bits.test_and_set(65, Ordering::AcqRel));
+        assert!(bits.test_and_set(65, Ordering::AcqRel));
+        assert!(bits.test_and_clear(65, Ordering::AcqRel));
+        assert!(!bits.test_and_clear(65, Ordering::AcqRel));
+
+        assert!(!bits.fetch_toggle(0, Ordering::SeqCst));
+        assert!(bits.fetch_toggle(0, Ordering::SeqCst));
+        assert!(!bits.get(0, Ordering::SeqCst));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range() {
+        AtomicBitSet::new(64).set(64, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn test_claim_first_zero() {
+        let bits = AtomicBitSet::new(130);
+        bits.set(0, Ordering::Relaxed);
+        bits.set(2, Ordering::Relaxed);
+
+        assert_eq!(Some(1), bits.claim_first_zero(Ordering::Acquire));
+        assert_eq!(Some(3), bits.claim_first_zero(Ordering::Acquire));
+
+        for position in 4..130 {
+            bits.set(position, Ordering::Relaxed);
+        }
+        assert_eq!(None, bits.claim_first_zero(Ordering::Acquire));
+
+        bits.clear(128, Ordering::Release);
+        assert_eq!(Some(128), bits.claim_first_zero(Ordering::Acquire));
+        assert_eq!(None, AtomicBitSet::new(0).claim_first_zero(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_concurrent_claims() {
+        let bits = AtomicBitSet::new(1000);
+
+        let claimed: Vec<Vec<usize>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..4).map(|_| {
+                scope.spawn(|| (0..250).map(|_| bits.claim_first_zero(Ordering::AcqRel).unwrap()).collect())
+            }).collect();
+            workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+        });
+
+        let mut all: Vec<usize> = claimed.into_iter().flatten().collect();
+        all.sort();
+        assert_eq!((0..1000).collect::<Vec<_>>(), all);
+        assert_eq!(None, bits.claim_first_zero(Ordering::AcqRel));
+    }
+
+    #[test]
+    fn test_concurrent_toggles() {
+        let bits = AtomicBitSet::new(64);
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1001 {
+                        bits.fetch_toggle(7, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        assert!(!bits.get(7, Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let set: BitSet = vec![1, 64, 200, 500].into_iter().collect();
+        let bits = AtomicBitSet::from_bit_set(&set, 300);
+
+        let snapshot = bits.snapshot(Ordering::Acquire);
+        assert_eq!(vec![1, 64, 200], snapshot.iter().collect::<Vec<_>>());
+        assert_eq!(vec![1, 64, 200], bits.ones().collect::<Vec<_>>());
+        assert!(bits.has_x_bit(64));
+        assert!(!bits.has_x_bit(500));
+        assert_eq!("{1, 64, 200}", format!("{:?}", bits));
+    }
+
+}
@@ -0,0 +1,436 @@
+//! A fixed-size array of bits that lives on the stack
+//!
+//! Stable Rust can not yet work out the number of backing words from the number of bits, so a
+//! `BitArray` takes both. `W` must always be `words_for_bits(N)`, which is checked when the array is
+//! created, so a mismatch fails to compile:
+//!
+//! ```
+//! use bit_utils::{words_for_bits, BitArray, BitInformation};
+//!
+//! type Flags = BitArray<100, { words_for_bits(100) }>;
+//!
+//! let mut flags = Flags::new();
+//! flags.set(99, true);
+//! assert_eq!(100, flags.number_of_bits());
+//! assert!(flags.has_x_bit(99));
+//! ```
+
+use core::fmt;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not};
+use core::ops::{Shl, ShlAssign, Shr, ShrAssign};
+
+use words::{self, words_for_bits};
+use {BitInformation, BitIteration, BitManipulation, BitOrdering, BitWidth};
+
+/// A fixed-size array of N bits, packed into W u64 words
+///
+/// Bit X lives in bit `X % 64` of word `X / 64`. Any bits in the last word past N are always zero.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitArray<const N: usize, const W: usize> {
+    words: [u64; W],
+}
+
+impl<const N: usize, const W: usize> BitArray<N, W> {
+
+    /// Fails to compile if W is not the right number of words for N bits
+    const VALID: () = assert!(W == words_for_bits(N), "W must be words_for_bits(N)");
+
+    /// Creates a BitArray with every bit clear
+    pub const fn new() -> BitArray<N, W> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        BitArray { words: [0; W] }
+    }
+
+    /// Creates a BitArray with every bit set to `value`
+    pub fn from_elem(value: bool) -> BitArray<N, W> {
+        let mut array = BitArray::new();
+        if value {
+            array.fill(0, N, true);
+        }
+        array
+    }
+
+    /// Gets the words backing this BitArray
+    pub fn as_words(&self) -> &[u64; W] {
+        &self.words
+    }
+
+    /// Gets the number of bits in this BitArray
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Checks to see if this BitArray has no bits
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Gets bit X, or None if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < N {
+            Some(self.words.has_x_bit(index))
+        } else {
+            None
+        }
+    }
+
+    /// Sets bit X to `value`, panicking if it is out of bounds
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < N, "bit index {} is out of range for length {}", index, N);
+        self.words.assign_x_bit(index, value);
+    }
+
+    /// Sets every bit from `start` up to (but not including) `end` to `value`
+    pub fn fill(&mut self, start: usize, end: usize, value: bool) {
+        assert!(start <= end && end <= N, "bit range {}..{} is out of range for length {}", start, end, N);
+        words::fill(&mut self.words, start, end, value);
+    }
+
+    /// Clears every bit
+    pub fn clear(&mut self) {
+        self.words = [0; W];
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Gets the number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        N - self.count_ones()
+    }
+
+    /// Checks to see if any bit is set
+    pub fn any(&self) -> bool {
+        self.words.iter().any(|&word| word != 0)
+    }
+
+    /// Checks to see if every bit is set
+    pub fn all(&self) -> bool {
+        self.count_ones() == N
+    }
+
+}
+
+impl<const N: usize, const W: usize> Default for BitArray<N, W> {
+    fn default() -> BitArray<N, W> {
+        BitArray::new()
+    }
+}
+
+impl<const N: usize, const W: usize> BitWidth for BitArray<N, W> {
+    const BITS: usize = N;
+}
+
+impl<const N: usize, const W: usize> BitInformation for BitArray<N, W> {
+
+    /// Gets the number of bits contained in this BitArray
+    fn number_of_bits(&self) -> usize {
+        N
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.get(position).unwrap_or(false)
+    }
+
+}
+
+impl<const N: usize, const W: usize> BitManipulation for BitArray<N, W> {
+
+    fn set_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.words.set_x_bit(position);
+        }
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.words.clear_x_bit(position);
+        }
+    }
+
+    fn toggle_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.words.toggle_x_bit(position);
+        }
+    }
+
+}
+
+impl<const N: usize, const W: usize> BitIteration for BitArray<N, W> {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_clear_bit(position).filter(|&found| found < N)
+    }
+
+}
+
+impl<const N: usize, const W: usize> BitOrdering for BitArray<N, W> {}
+
+impl<const N: usize, const W: usize> Index<usize> for BitArray<N, W> {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &bool {
+        match self.get(index) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!("bit index {} is out of range for length {}", index, N),
+        }
+    }
+}
+
+impl<const N: usize, const W: usize> BitAndAssign for BitArray<N, W> {
+    fn bitand_assign(&mut self, other: BitArray<N, W>) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word &= *other;
+        }
+    }
+}
+
+impl<const N: usize, const W: usize> BitOrAssign for BitArray<N, W> {
+    fn bitor_assign(&mut self, other: BitArray<N, W>) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= *other;
+        }
+    }
+}
+
+impl<const N: usize, const W: usize> BitXorAssign for BitArray<N, W> {
+    fn bitxor_assign(&mut self, other: BitArray<N, W>) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word ^= *other;
+        }
+    }
+}
+
+impl<const N: usize, const W: usize> BitAnd for BitArray<N, W> {
+    type Output = BitArray<N, W>;
+
+    fn bitand(mut self, other: BitArray<N, W>) -> BitArray<N, W> {
+        self &= other;
+        self
+    }
+}
+
+impl<const N: usize, const W: usize> BitOr for BitArray<N, W> {
+    type Output = BitArray<N, W>;
+
+    fn bitor(mut self, other: BitArray<N, W>) -> BitArray<N, W> {
+        self |= other;
+        self
+    }
+}
+
+impl<const N: usize, const W: usize> BitXor for BitArray<N, W> {
+    type Output = BitArray<N, W>;
+
+    fn bitxor(mut self, other: BitArray<N, W>) -> BitArray<N, W> {
+        self ^= other;
+        self
+    }
+}
+
+impl<const N: usize, const W: usize> Not for BitArray<N, W> {
+    type Output = BitArray<N, W>;
+
+    fn not(mut self) -> BitArray<N, W> {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+        words::clear_unused_bits(&mut self.words, N);
+        self
+    }
+}
+
+impl<const N: usize, const W: usize> ShlAssign<usize> for BitArray<N, W> {
+
+    /// Moves bit X up to bit X + `amount`, dropping the bits that move past N
+    fn shl_assign(&mut self, amount: usize) {
+        words::shift_up(&mut self.words, amount);
+        words::clear_unused_bits(&mut self.words, N);
+    }
+
+}
+
+impl<const N: usize, const W: usize> ShrAssign<usize> for BitArray<N, W> {
+
+    /// Moves bit X down to bit X - `amount`, dropping the bits that move below bit 0
+    fn shr_assign(&mut self, amount: usize) {
+        words::shift_down(&mut self.words, amount);
+    }
+
+}
+
+impl<const N: usize, const W: usize> Shl<usize> for BitArray<N, W> {
+    type Output = BitArray<N, W>;
+
+    fn shl(mut self, amount: usize) -> BitArray<N, W> {
+        self <<= amount;
+        self
+    }
+}
+
+impl<const N: usize, const W: usize> Shr<usize> for BitArray<N, W> {
+    type Output = BitArray<N, W>;
+
+    fn shr(mut self, amount: usize) -> BitArray<N, W> {
+        self >>= amount;
+        self
+    }
+}
+
+impl<const N: usize, const W: usize> fmt::Debug for BitArray<N, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BitArray[")?;
+        for bit in self.bits() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+    use {number_of_bits, BitError};
+
+    type Flags = BitArray<100, { words_for_bits(100) }>;
+
+    #[test]
+    fn test_words_for_bits() {
+        assert_eq!(0, words_for_bits(0));
+        assert_eq!(1, words_for_bits(1));
+        assert_eq!(1, words_for_bits(64));
+        assert_eq!(2, words_for_bits(65));
+        assert_eq!(2, words_for_bits(100));
+    }
+
+    #[test]
+    fn test_number_of_bits() {
+        assert_eq!(100, Flags::new().number_of_bits());
+        assert_eq!(100, number_of_bits::<Flags>());
+        assert_eq!(100, Flags::new().len());
+        assert_eq!(0, BitArray::<0, 0>::new().number_of_bits());
+        assert!(BitArray::<0, 0>::new().is_empty());
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut flags = Flags::new();
+
+        flags.set(0, true);
+        flags.set(64, true);
+        flags.set(99, true);
+        assert_eq!(Some(true), flags.get(99));
+        assert_eq!(Some(false), flags.get(98));
+        assert_eq!(None, flags.get(100));
+        assert!(flags[64]);
+        assert!(!flags[63]);
+        assert_eq!([1, (1 << 35) | 1], *flags.as_words());
+
+        flags.set(64, false);
+        assert_eq!(2, flags.count_ones());
+        assert_eq!(98, flags.count_zeros());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_out_of_range() {
+        Flags::new().set(100, true);
+    }
+
+    #[test]
+    fn test_bit_traits() {
+        let mut flags = Flags::new();
+
+        flags.set_x_bit(70);
+        flags.set_x_bit(100);
+        assert!(flags.has_x_bit(70));
+        assert!(!flags.has_x_bit(100));
+        assert_eq!(1, flags.count_ones());
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 100, number_of_bits: 100 }), flags.try_set_x_bit(100));
+
+        flags.toggle_x_bit(99);
+        assert!(flags.has_most_signifigant_bit());
+        assert_eq!(vec![70, 99], flags.ones().collect::<Vec<_>>());
+        assert_eq!(98, flags.zeros().count());
+        assert_eq!(None, Flags::from_elem(true).next_clear_bit(0));
+        assert_eq!(vec![(0, 70, false), (70, 1, true), (71, 28, false), (99, 1, true)], flags.runs().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut flags = Flags::new();
+
+        flags.fill(60, 70, true);
+        assert_eq!((60..70).collect::<Vec<_>>(), flags.ones().collect::<Vec<_>>());
+        flags.fill(62, 68, false);
+        assert_eq!(vec![60, 61, 68, 69], flags.ones().collect::<Vec<_>>());
+
+        let full = Flags::from_elem(true);
+        assert!(full.all());
+        assert_eq!(100, full.count_ones());
+        assert_eq!([u64::MAX, (1 << 36) - 1], *full.as_words());
+
+        flags.clear();
+        assert!(!flags.any());
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let mut a = Flags::new();
+        let mut b = Flags::new();
+        a.fill(0, 80, true);
+        b.fill(40, 100, true);
+
+        assert_eq!((40..80).collect::<Vec<_>>(), (a & b).ones().collect::<Vec<_>>());
+        assert_eq!(100, (a | b).count_ones());
+        assert_eq!(60, (a ^ b).count_ones());
+        assert_eq!((80..100).collect::<Vec<_>>(), (!a).ones().collect::<Vec<_>>());
+        assert_eq!(Flags::from_elem(true), !Flags::new());
+
+        let mut c = a;
+        c ^= a;
+        assert_eq!(Flags::default(), c);
+        c |= b;
+        c &= a;
+        assert_eq!(a & b, c);
+    }
+
+    #[test]
+    fn test_shift_operators() {
+        let mut flags = Flags::new();
+        flags.set(0, true);
+        flags.set(63, true);
+        flags.set(98, true);
+
+        assert_eq!(vec![1, 64, 99], (flags << 1).ones().collect::<Vec<_>>());
+        assert_eq!(vec![36, 99], (flags << 36).ones().collect::<Vec<_>>());
+        assert_eq!(vec![0, 35], (flags >> 63).ones().collect::<Vec<_>>());
+        assert_eq!(Flags::new(), flags << 100);
+        assert_eq!(Flags::new(), flags >> 100);
+        assert_eq!([u64::MAX << 1, (1 << 36) - 1], *(Flags::from_elem(true) << 1).as_words());
+
+        flags <<= 2;
+        assert_eq!(vec![2, 65], flags.ones().collect::<Vec<_>>());
+        flags >>= 65;
+        assert_eq!(vec![0], flags.ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut flags = BitArray::<4, 1>::new();
+        flags.set(1, true);
+
+        assert_eq!("BitArray[0100]", format!("{:?}", flags));
+    }
+
+}
@@ -0,0 +1,883 @@
+//! A two-dimensional grid of bits, packed into u64 words row by row
+//!
+//! Each row starts on a fresh word, so row X is the words `X * words_per_row` up to
+//! `(X + 1) * words_per_row`, and column Y of a row is bit `Y % 64` of its word `Y / 64`. Bits past
+//! the last column of each row are always zero. Keeping rows word aligned means operations on
+//! whole rows work a word at a time, and `transpose` can swap 64 by 64 blocks at once.
+//!
+//! A BitMatrix is also a matrix over GF(2), the field with just 0 and 1, where adding is XOR and
+//! multiplying is AND. `multiply`, `rank`, `inverse`, `null_space` and `solve` all work with whole
+//! rows at a time through Gauss-Jordan elimination.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Index, Mul, Range};
+
+use words::{self, words_for_bits, WORD_BITS};
+use {BitInformation, BitIteration, BitVec};
+
+/// Transposes a 64 by 64 block of bits in place, where bit Y of word X is row X, column Y
+///
+/// This swaps the off-diagonal 32 by 32 quarters, then the 16 by 16 quarters inside each of those,
+/// and so on down to single bits.
+fn transpose_block(block: &mut [u64; WORD_BITS]) {
+    let mut width = 32;
+    let mut mask: u64 = 0x0000_0000_FFFF_FFFF;
+
+    while width != 0 {
+        let mut row = 0;
+        while row < WORD_BITS {
+            let swap = ((block[row] >> width) ^ block[row + width]) & mask;
+            block[row] ^= swap << width;
+            block[row + width] ^= swap;
+            row = (row + width + 1) & !width;
+        }
+
+        width >>= 1;
+        mask ^= mask << width;
+    }
+}
+
+/// A grid of bits with a fixed number of rows and columns
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    words: Vec<u64>,
+    rows: usize,
+    columns: usize,
+}
+
+impl BitMatrix {
+
+    /// Creates a BitMatrix of clear bits with X rows and Y columns
+    pub fn new(rows: usize, columns: usize) -> BitMatrix {
+        BitMatrix { words: vec![0; rows * words_for_bits(columns)], rows, columns }
+    }
+
+    /// Creates a square BitMatrix with X rows and columns, with only the diagonal set
+    pub fn identity(size: usize) -> BitMatrix {
+        let mut matrix = BitMatrix::new(size, size);
+        for index in 0..size {
+            matrix.set(index, index, true);
+        }
+        matrix
+    }
+
+    /// Gets the words backing this BitMatrix, row by row
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Gets the number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Gets the number of columns
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Checks to see if this BitMatrix has no bits
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0 || self.columns == 0
+    }
+
+    /// Gets the bit at (row, column), or None if it is out of bounds
+    pub fn get(&self, row: usize, column: usize) -> Option<bool> {
+        if row < self.rows && column < self.columns {
+            Some(self.row_words(row).has_x_bit(column))
+        } else {
+            None
+        }
+    }
+
+    /// Sets the bit at (row, column), panicking if it is out of bounds
+    pub fn set(&mut self, row: usize, column: usize, value: bool) {
+        self.check_position(row, column);
+
+        let word = &mut self.row_words_mut(row)[column / WORD_BITS];
+        let mask = 1 << (column % WORD_BITS);
+        if value {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+
+    /// Flips the bit at (row, column), panicking if it is out of bounds
+    pub fn toggle(&mut self, row: usize, column: usize) {
+        self.check_position(row, column);
+        self.row_words_mut(row)[column / WORD_BITS] ^= 1 << (column % WORD_BITS);
+    }
+
+    /// Gets a view of row X, panicking if it is out of bounds
+    pub fn row(&self, row: usize) -> Row<'_> {
+        assert!(row < self.rows, "row {} is out of range for {} rows", row, self.rows);
+        Row { words: self.row_words(row), len: self.columns }
+    }
+
+    /// Gets a view of column X, panicking if it is out of bounds
+    pub fn column(&self, column: usize) -> Column<'_> {
+        assert!(column < self.columns, "column {} is out of range for {} columns", column, self.columns);
+        Column { matrix: self, column }
+    }
+
+    /// Iterates over every row from the top
+    pub fn iter_rows(&self) -> Rows<'_> {
+        Rows { matrix: self, front: 0, back: self.rows }
+    }
+
+    /// Iterates over every column from the left
+    pub fn iter_columns(&self) -> Columns<'_> {
+        Columns { matrix: self, front: 0, back: self.columns }
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Sets every bit in a block of rows and columns to `value`, panicking if the block goes past
+    /// the edge of the matrix
+    pub fn fill_region(&mut self, rows: Range<usize>, columns: Range<usize>, value: bool) {
+        assert!(
+            rows.start <= rows.end && rows.end <= self.rows && columns.start <= columns.end && columns.end <= self.columns,
+            "region {}..{} by {}..{} is out of range for a {} by {} matrix",
+            rows.start, rows.end, columns.start, columns.end, self.rows, self.columns
+        );
+
+        for row in rows {
+            words::fill(self.row_words_mut(row), columns.start, columns.end, value);
+        }
+    }
+
+    /// Gets a new BitMatrix with the rows and columns swapped
+    pub fn transpose(&self) -> BitMatrix {
+        let mut result = BitMatrix::new(self.columns, self.rows);
+        let mut block = [0u64; WORD_BITS];
+
+        for row_block in 0..words_for_bits(self.rows) {
+            for column_block in 0..words_for_bits(self.columns) {
+                for (offset, word) in block.iter_mut().enumerate() {
+                    let row = row_block * WORD_BITS + offset;
+                    *word = if row < self.rows { self.row_words(row)[column_block] } else { 0 };
+                }
+
+                transpose_block(&mut block);
+
+                for (offset, &word) in block.iter().enumerate() {
+                    let row = column_block * WORD_BITS + offset;
+                    if row < result.rows {
+                        result.row_words_mut(row)[row_block] = word;
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Swaps two rows, panicking if either is out of bounds
+    pub fn swap_rows(&mut self, first: usize, second: usize) {
+        self.check_row(first);
+        self.check_row(second);
+
+        let stride = self.words_per_row();
+        for word in 0..stride {
+            self.words.swap(first * stride + word, second * stride + word);
+        }
+    }
+
+    /// ANDs row `source` into row `target`, panicking if either is out of bounds
+    pub fn and_row(&mut self, target: usize, source: usize) {
+        self.combine_rows(target, source, |ours, theirs| ours & theirs);
+    }
+
+    /// ORs row `source` into row `target`, panicking if either is out of bounds
+    pub fn or_row(&mut self, target: usize, source: usize) {
+        self.combine_rows(target, source, |ours, theirs| ours | theirs);
+    }
+
+    /// XORs row `source` into row `target`, panicking if either is out of bounds
+    pub fn xor_row(&mut self, target: usize, source: usize) {
+        self.combine_rows(target, source, |ours, theirs| ours ^ theirs);
+    }
+
+    /// Multiplies this matrix by another over GF(2), panicking if this matrix does not have as
+    /// many columns as the other has rows
+    ///
+    /// Row X of the result is the XOR of every row of the other matrix picked out by a set bit in
+    /// row X of this one.
+    pub fn multiply(&self, other: &BitMatrix) -> BitMatrix {
+        assert!(
+            self.columns == other.rows,
+            "cannot multiply a {} by {} matrix by a {} by {} matrix",
+            self.rows, self.columns, other.rows, other.columns
+        );
+
+        let mut result = BitMatrix::new(self.rows, other.columns);
+        for row in 0..self.rows {
+            for inner in self.row(row).ones() {
+                for (word, &theirs) in result.row_words_mut(row).iter_mut().zip(other.row_words(inner)) {
+                    *word ^= theirs;
+                }
+            }
+        }
+        result
+    }
+
+    /// Multiplies this matrix by a column vector over GF(2), panicking if the vector does not have
+    /// one bit per column
+    pub fn multiply_vec(&self, vector: &BitVec) -> BitVec {
+        assert!(
+            vector.len() == self.columns,
+            "cannot multiply a {} by {} matrix by a vector of length {}",
+            self.rows, self.columns, vector.len()
+        );
+
+        (0..self.rows)
+            .map(|row| {
+                let words = self.row_words(row).iter().zip(vector.as_words());
+                words.map(|(&ours, &theirs)| (ours & theirs).count_ones()).sum::<u32>() % 2 == 1
+            })
+            .collect()
+    }
+
+    /// Puts this matrix in reduced row echelon form with Gauss-Jordan elimination, returning its
+    /// rank
+    ///
+    /// Afterwards the first `rank` rows each start with a set bit, called the pivot, which is the
+    /// only set bit in its column, and every other row is clear.
+    pub fn row_reduce(&mut self) -> usize {
+        self.eliminate(None)
+    }
+
+    /// Gets the rank of this matrix over GF(2), which is the number of linearly independent rows
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce()
+    }
+
+    /// Gets the inverse of this matrix over GF(2), or None if it is not square or is singular
+    pub fn inverse(&self) -> Option<BitMatrix> {
+        if self.rows != self.columns {
+            return None;
+        }
+
+        let mut inverse = BitMatrix::identity(self.rows);
+        if self.clone().eliminate(Some(&mut inverse)) == self.rows {
+            Some(inverse)
+        } else {
+            None
+        }
+    }
+
+    /// Gets a basis for the null space of this matrix, the vectors X with `A * X = 0`
+    ///
+    /// There is one vector for each column without a pivot, with that column's bit set.
+    pub fn null_space(&self) -> Vec<BitVec> {
+        let mut reduced = self.clone();
+        let rank = reduced.row_reduce();
+        let pivots: Vec<usize> = (0..rank).filter_map(|row| reduced.row(row).next_set_bit(0)).collect();
+
+        let mut basis = Vec::with_capacity(self.columns - rank);
+        let mut pivot = pivots.iter().peekable();
+        for column in 0..self.columns {
+            if pivot.peek() == Some(&&column) {
+                pivot.next();
+                continue;
+            }
+
+            let mut vector = BitVec::from_elem(self.columns, false);
+            vector.set(column, true);
+            for (row, &pivot_column) in pivots.iter().enumerate() {
+                if reduced.row_words(row).has_x_bit(column) {
+                    vector.set(pivot_column, true);
+                }
+            }
+            basis.push(vector);
+        }
+
+        basis
+    }
+
+    /// Finds a vector X with `A * X = B` over GF(2), or None if there is no such vector, panicking
+    /// if B does not have one bit per row
+    ///
+    /// When there is more than one answer, every column without a pivot is left clear.
+    pub fn solve(&self, target: &BitVec) -> Option<BitVec> {
+        assert!(
+            target.len() == self.rows,
+            "cannot solve a {} by {} matrix for a vector of length {}",
+            self.rows, self.columns, target.len()
+        );
+
+        let mut reduced = self.clone();
+        let mut augmented = BitMatrix::new(self.rows, 1);
+        for row in target.ones() {
+            augmented.set(row, 0, true);
+        }
+
+        let rank = reduced.eliminate(Some(&mut augmented));
+        if (rank..self.rows).any(|row| augmented.row_words(row)[0] != 0) {
+            return None;
+        }
+
+        let mut solution = BitVec::from_elem(self.columns, false);
+        for row in 0..rank {
+            if augmented.row_words(row)[0] != 0 {
+                solution.set(reduced.row(row).next_set_bit(0)?, true);
+            }
+        }
+        Some(solution)
+    }
+
+    /// Runs Gauss-Jordan elimination, applying every row operation to `companion` as well, and
+    /// returns the rank
+    fn eliminate(&mut self, mut companion: Option<&mut BitMatrix>) -> usize {
+        let mut rank = 0;
+
+        for column in 0..self.columns {
+            if rank == self.rows {
+                break;
+            }
+
+            let pivot = match (rank..self.rows).find(|&row| self.row_words(row).has_x_bit(column)) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+
+            self.swap_rows(rank, pivot);
+            if let Some(ref mut companion) = companion {
+                companion.swap_rows(rank, pivot);
+            }
+
+            for row in 0..self.rows {
+                if row != rank && self.row_words(row).has_x_bit(column) {
+                    self.xor_row(row, rank);
+                    if let Some(ref mut companion) = companion {
+                        companion.xor_row(row, rank);
+                    }
+                }
+            }
+
+            rank += 1;
+        }
+
+        rank
+    }
+
+    /// Gets the number of words each row takes up
+    fn words_per_row(&self) -> usize {
+        words_for_bits(self.columns)
+    }
+
+    /// Gets the words of row X
+    fn row_words(&self, row: usize) -> &[u64] {
+        let stride = self.words_per_row();
+        &self.words[row * stride..(row + 1) * stride]
+    }
+
+    /// Gets the words of row X mutably
+    fn row_words_mut(&mut self, row: usize) -> &mut [u64] {
+        let stride = self.words_per_row();
+        &mut self.words[row * stride..(row + 1) * stride]
+    }
+
+    /// Replaces each word of row `target` with `operation` applied to it and the same word of row
+    /// `source`
+    fn combine_rows<F: Fn(u64, u64) -> u64>(&mut self, target: usize, source: usize, operation: F) {
+        self.check_row(target);
+        self.check_row(source);
+
+        let stride = self.words_per_row();
+        for word in 0..stride {
+            let theirs = self.words[source * stride + word];
+            let ours = &mut self.words[target * stride + word];
+            *ours = operation(*ours, theirs);
+        }
+    }
+
+    /// Panics if row X is out of bounds
+    fn check_row(&self, row: usize) {
+        assert!(row < self.rows, "row {} is out of range for {} rows", row, self.rows);
+    }
+
+    /// Panics if (row, column) is out of bounds
+    fn check_position(&self, row: usize, column: usize) {
+        assert!(
+            row < self.rows && column < self.columns,
+            "bit ({}, {}) is out of range for a {} by {} matrix",
+            row, column, self.rows, self.columns
+        );
+    }
+
+}
+
+impl Index<(usize, usize)> for BitMatrix {
+    type Output = bool;
+
+    fn index(&self, (row, column): (usize, usize)) -> &bool {
+        match self.get(row, column) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!("bit ({}, {}) is out of range for a {} by {} matrix", row, column, self.rows, self.columns),
+        }
+    }
+}
+
+impl<'b> Mul<&'b BitMatrix> for &BitMatrix {
+    type Output = BitMatrix;
+
+    fn mul(self, other: &'b BitMatrix) -> BitMatrix {
+        self.multiply(other)
+    }
+}
+
+impl fmt::Debug for BitMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BitMatrix[")?;
+        for (index, row) in self.iter_rows().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            for bit in row.bits() {
+                f.write_str(if bit { "1" } else { "0" })?;
+            }
+        }
+        f.write_str("]")
+    }
+}
+
+/// A view of one row of a BitMatrix
+#[derive(Clone, Copy)]
+pub struct Row<'a> {
+    words: &'a [u64],
+    len: usize,
+}
+
+impl<'a> Row<'a> {
+
+    /// Gets the words backing this row, with any bits past the last column clear
+    pub fn as_words(&self) -> &'a [u64] {
+        self.words
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+}
+
+impl<'a> BitInformation for Row<'a> {
+
+    fn number_of_bits(&self) -> usize {
+        self.len
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        position < self.len && self.words.has_x_bit(position)
+    }
+
+}
+
+impl<'a> BitIteration for Row<'a> {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_clear_bit(position).filter(|&position| position < self.len)
+    }
+
+}
+
+impl<'a> fmt::Debug for Row<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Row[")?;
+        for bit in self.bits() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// A view of one column of a BitMatrix, read from the top down
+#[derive(Clone, Copy)]
+pub struct Column<'a> {
+    matrix: &'a BitMatrix,
+    column: usize,
+}
+
+impl<'a> Column<'a> {
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.ones().count()
+    }
+
+}
+
+impl<'a> BitInformation for Column<'a> {
+
+    fn number_of_bits(&self) -> usize {
+        self.matrix.rows
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.matrix.get(position, self.column) == Some(true)
+    }
+
+}
+
+impl<'a> BitIteration for Column<'a> {}
+
+impl<'a> fmt::Debug for Column<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Column[")?;
+        for bit in self.bits() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// An iterator over the rows of a BitMatrix
+#[derive(Clone, Debug)]
+pub struct Rows<'a> {
+    matrix: &'a BitMatrix,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Rows<'a> {
+    type Item = Row<'a>;
+
+    fn next(&mut self) -> Option<Row<'a>> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(self.matrix.row(self.front - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Rows<'a> {
+    fn next_back(&mut self) -> Option<Row<'a>> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(self.matrix.row(self.back))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for Rows<'a> {}
+
+/// An iterator over the columns of a BitMatrix
+#[derive(Clone, Debug)]
+pub struct Columns<'a> {
+    matrix: &'a BitMatrix,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Columns<'a> {
+    type Item = Column<'a>;
+
+    fn next(&mut self) -> Option<Column<'a>> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(self.matrix.column(self.front - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Columns<'a> {
+    fn next_back(&mut self) -> Option<Column<'a>> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(self.matrix.column(self.back))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for Columns<'a> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Builds a matrix from rows written as strings of 0s and 1s
+    fn matrix(rows: &[&str]) -> BitMatrix {
+        let mut result = BitMatrix::new(rows.len(), rows.first().map_or(0, |row| row.len()));
+        for (row, bits) in rows.iter().enumerate() {
+            for (column, bit) in bits.chars().enumerate() {
+                result.set(row, column, bit == '1');
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let mut grid = BitMatrix::new(3, 70);
+        assert_eq!(6, grid.as_words().len());
+
+        grid.set(1, 69, true);
+        grid.set(2, 0, true);
+        grid.toggle(0, 5);
+        assert_eq!(Some(true), grid.get(1, 69));
+        assert_eq!(Some(false), grid.get(1, 68));
+        assert_eq!(None, grid.get(3, 0));
+        assert_eq!(None, grid.get(0, 70));
+        assert!(grid[(2, 0)]);
+        assert!(grid[(0, 5)]);
+        assert_eq!(3, grid.count_ones());
+
+        grid.set(1, 69, false);
+        grid.toggle(0, 5);
+        assert_eq!(1, grid.count_ones());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_out_of_range() {
+        BitMatrix::new(2, 2).set(0, 2, true);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = matrix(&["1010", "0110", "0001"]);
+
+        assert_eq!(vec![0, 2], grid.row(0).ones().collect::<Vec<_>>());
+        assert_eq!(vec![0, 3], grid.row(1).zeros().collect::<Vec<_>>());
+        assert_eq!(vec![0, 1], grid.column(2).ones().collect::<Vec<_>>());
+        assert_eq!(vec![false, false, true], grid.column(3).bits().collect::<Vec<_>>());
+
+        assert_eq!(3, grid.iter_rows().len());
+        assert_eq!(vec![2, 2, 1], grid.iter_rows().map(|row| row.count_ones()).collect::<Vec<_>>());
+        assert_eq!(vec![1, 1, 2, 1], grid.iter_columns().map(|column| column.count_ones()).collect::<Vec<_>>());
+        assert_eq!(&[0b1000], grid.iter_rows().next_back().unwrap().as_words());
+    }
+
+    #[test]
+    fn test_transpose_block() {
+        let mut block = [0u64; 64];
+        for (row, word) in block.iter_mut().enumerate() {
+            *word = (row as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        }
+
+        let original = block;
+        transpose_block(&mut block);
+        for (row, word) in original.iter().enumerate() {
+            for (column, transposed) in block.iter().enumerate() {
+                assert_eq!(word.has_x_bit(column), transposed.has_x_bit(row));
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut grid = BitMatrix::new(130, 75);
+        for row in 0..130 {
+            for column in 0..75 {
+                grid.set(row, column, (row * 7 + column * 3) % 5 == 0);
+            }
+        }
+
+        let transposed = grid.transpose();
+        assert_eq!(75, transposed.rows());
+        assert_eq!(130, transposed.columns());
+        for row in 0..130 {
+            for column in 0..75 {
+                assert_eq!(grid.get(row, column), transposed.get(column, row));
+            }
+        }
+        assert_eq!(grid, transposed.transpose());
+        assert_eq!(BitMatrix::new(0, 3), BitMatrix::new(3, 0).transpose());
+    }
+
+    #[test]
+    fn test_row_operations() {
+        let mut grid = matrix(&["1100", "1010", "0000"]);
+
+        grid.xor_row(2, 0);
+        assert_eq!(matrix(&["1100", "1010", "1100"]), grid);
+
+        grid.and_row(2, 1);
+        assert_eq!(matrix(&["1100", "1010", "1000"]), grid);
+
+        grid.or_row(0, 1);
+        assert_eq!(matrix(&["1110", "1010", "1000"]), grid);
+
+        grid.swap_rows(0, 2);
+        assert_eq!(matrix(&["1000", "1010", "1110"]), grid);
+
+        grid.xor_row(1, 1);
+        assert_eq!(matrix(&["1000", "0000", "1110"]), grid);
+    }
+
+    #[test]
+    fn test_fill_region() {
+        let mut image = BitMatrix::new(4, 100);
+
+        image.fill_region(1..3, 60..70, true);
+        assert_eq!(20, image.count_ones());
+        assert_eq!(vec![60, 61, 62, 63, 64, 65, 66, 67, 68, 69], image.row(2).ones().collect::<Vec<_>>());
+        assert_eq!(0, image.row(3).count_ones());
+
+        image.fill_region(0..4, 0..100, true);
+        assert_eq!(400, image.count_ones());
+        assert_eq!(None, image.row(0).next_clear_bit(0));
+
+        image.fill_region(2..2, 0..100, false);
+        image.fill_region(0..4, 50..51, false);
+        assert_eq!(396, image.count_ones());
+        assert_eq!(vec![1, 1, 1, 1], image.column(50).zeros().map(|_| 1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fill_region_out_of_range() {
+        BitMatrix::new(4, 4).fill_region(0..5, 0..4, true);
+    }
+
+    /// Builds a square matrix full of arbitrary bits
+    fn scrambled(size: usize, seed: u64) -> BitMatrix {
+        let mut state = seed;
+        let mut result = BitMatrix::new(size, size);
+        for row in 0..size {
+            for column in 0..size {
+                state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+                result.set(row, column, state >> 63 == 1);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_multiply() {
+        let left = matrix(&["110", "011"]);
+        let right = matrix(&["10", "11", "01"]);
+
+        assert_eq!(matrix(&["01", "10"]), left.multiply(&right));
+        assert_eq!(matrix(&["110", "101", "011"]), &right * &left);
+        assert_eq!(left, &BitMatrix::identity(2) * &left);
+        assert_eq!(left, &left * &BitMatrix::identity(3));
+
+        let vector: BitVec = vec![true, true, true].into_iter().collect();
+        assert_eq!(vec![false, false], left.multiply_vec(&vector).bits().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_multiply_mismatched() {
+        BitMatrix::new(2, 3).multiply(&BitMatrix::new(2, 3));
+    }
+
+    #[test]
+    fn test_lfsr_period() {
+        // The companion matrix of x^4 + x + 1, which is primitive, so stepping the register 15
+        // times gets back to where it started
+        let step = matrix(&["0001", "1001", "0100", "0010"]);
+
+        let mut power = step.clone();
+        for _ in 1..15 {
+            assert_ne!(BitMatrix::identity(4), power);
+            power = &power * &step;
+        }
+        assert_eq!(BitMatrix::identity(4), power);
+    }
+
+    #[test]
+    fn test_row_reduce_and_rank() {
+        let mut grid = matrix(&["1101", "0110", "1011", "1111"]);
+        assert_eq!(3, grid.rank());
+
+        assert_eq!(3, grid.row_reduce());
+        assert_eq!(matrix(&["1001", "0100", "0010", "0000"]), grid);
+
+        assert_eq!(0, BitMatrix::new(3, 5).rank());
+        assert_eq!(100, BitMatrix::identity(100).rank());
+    }
+
+    #[test]
+    fn test_inverse() {
+        for seed in 0..20 {
+            let grid = scrambled(70, seed);
+            match grid.inverse() {
+                Some(inverse) => {
+                    assert_eq!(70, grid.rank());
+                    assert_eq!(BitMatrix::identity(70), &grid * &inverse);
+                    assert_eq!(BitMatrix::identity(70), &inverse * &grid);
+                }
+                None => assert!(grid.rank() < 70),
+            }
+        }
+
+        assert_eq!(None, matrix(&["11", "11"]).inverse());
+        assert_eq!(None, BitMatrix::new(2, 3).inverse());
+        assert_eq!(Some(BitMatrix::new(0, 0)), BitMatrix::new(0, 0).inverse());
+    }
+
+    #[test]
+    fn test_null_space() {
+        let grid = matrix(&["1101", "0110", "1011"]);
+        let basis = grid.null_space();
+
+        assert_eq!(4 - grid.rank(), basis.len());
+        for vector in &basis {
+            assert_eq!(0, grid.multiply_vec(vector).count_ones());
+        }
+        assert_eq!(vec![true, true, true, false], basis[0].bits().collect::<Vec<_>>());
+
+        assert!(BitMatrix::identity(5).null_space().is_empty());
+        assert_eq!(3, BitMatrix::new(2, 3).null_space().len());
+    }
+
+    #[test]
+    fn test_solve() {
+        let grid = scrambled(90, 7);
+        let expected: BitVec = (0..90).map(|index| index % 3 == 0).collect();
+        let target = grid.multiply_vec(&expected);
+
+        let solution = grid.solve(&target).unwrap();
+        assert_eq!(target, grid.multiply_vec(&solution));
+
+        let singular = matrix(&["110", "011", "101"]);
+        let reachable: BitVec = vec![true, false, true].into_iter().collect();
+        let unreachable: BitVec = vec![true, false, false].into_iter().collect();
+        assert_eq!(reachable, singular.multiply_vec(&singular.solve(&reachable).unwrap()));
+        assert_eq!(None, singular.solve(&unreachable));
+    }
+
+    #[test]
+    fn test_debug() {
+        let grid = matrix(&["10", "01"]);
+
+        assert_eq!("BitMatrix[10, 01]", format!("{:?}", grid));
+        assert_eq!("Row[01]", format!("{:?}", grid.row(1)));
+        assert_eq!("Column[10]", format!("{:?}", grid.column(0)));
+    }
+
+}
@@ -0,0 +1,510 @@
+//! A set of small unsigned integers, stored as a bitmap
+//!
+//! Member X is bit X of a `Vec<u64>`, so the set grows to fit the largest member inserted. Two
+//! sets are equal when they hold the same members, however much room either has.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Bound, RangeBounds, Sub, SubAssign};
+
+use words::{words_for_bits, WORD_BITS};
+use {BitInformation, BitIteration, BitManipulation, BitQueries};
+
+/// A set of `usize` members, iterated in ascending order
+#[derive(Clone, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+
+    /// Creates an empty BitSet
+    pub fn new() -> BitSet {
+        BitSet { words: Vec::new() }
+    }
+
+    /// Creates an empty BitSet with room for members below `capacity`
+    pub fn with_capacity(capacity: usize) -> BitSet {
+        let mut set = BitSet::new();
+        set.grow_to(words_for_bits(capacity));
+        set
+    }
+
+    /// Creates a BitSet whose members are the set bits of some words
+    pub fn from_words(words: &[u64]) -> BitSet {
+        BitSet { words: words.to_vec() }
+    }
+
+    /// Gets the number of members this BitSet can hold without growing
+    pub fn capacity(&self) -> usize {
+        self.words.len() * WORD_BITS
+    }
+
+    /// Gets the number of members
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Checks to see if this BitSet has no members
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Checks to see if X is a member
+    pub fn contains(&self, value: usize) -> bool {
+        self.words.has_x_bit(value)
+    }
+
+    /// Adds X as a member, returning true if it was not already one
+    pub fn insert(&mut self, value: usize) -> bool {
+        if !self.words.is_bit_in_bounds(value) {
+            self.words.resize(value / WORD_BITS + 1, 0);
+        } else if self.words.has_x_bit(value) {
+            return false;
+        }
+
+        self.words.set_x_bit(value);
+        true
+    }
+
+    /// Removes X as a member, returning true if it was one
+    pub fn remove(&mut self, value: usize) -> bool {
+        if self.words.has_x_bit(value) {
+            self.words.clear_x_bit(value);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes every member
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    /// Gets the smallest member, or None if the set is empty
+    pub fn min(&self) -> Option<usize> {
+        self.words.next_set_bit(0)
+    }
+
+    /// Gets the largest member, or None if the set is empty
+    pub fn max(&self) -> Option<usize> {
+        let (index, word) = self.words.iter().enumerate().rev().find(|&(_, &word)| word != 0)?;
+        word.highest_set_bit().map(|position| index * WORD_BITS + position)
+    }
+
+    /// Iterates over every member in ascending order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { words: &self.words, position: 0, end: usize::MAX }
+    }
+
+    /// Iterates over the members inside a range in ascending order
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_> {
+        let position = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => usize::MAX,
+        };
+
+        Iter { words: &self.words, position, end }
+    }
+
+    /// Gets a new BitSet holding the members of either set
+    pub fn union(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    /// Gets a new BitSet holding the members of both sets
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    /// Gets a new BitSet holding the members of this set that are not in the other
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.difference_with(other);
+        result
+    }
+
+    /// Gets a new BitSet holding the members of exactly one of the sets
+    pub fn symmetric_difference(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.symmetric_difference_with(other);
+        result
+    }
+
+    /// Adds every member of the other set to this one
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.grow_to(other.words.len());
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= *other;
+        }
+    }
+
+    /// Removes every member that is not also in the other set
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.words.truncate(other.words.len());
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word &= *other;
+        }
+    }
+
+    /// Removes every member of the other set from this one
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word &= !*other;
+        }
+    }
+
+    /// Keeps the members that are in exactly one of the sets
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.grow_to(other.words.len());
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word ^= *other;
+        }
+    }
+
+    /// Checks to see if every member of this set is in the other
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words.iter().enumerate().all(|(index, &word)| word & !other.word(index) == 0)
+    }
+
+    /// Checks to see if every member of the other set is in this one
+    pub fn is_superset(&self, other: &BitSet) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Checks to see if the sets have no members in common
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(other.words.iter()).all(|(&word, &other)| word & other == 0)
+    }
+
+    /// Gets word X, treating words past the end as empty
+    fn word(&self, index: usize) -> u64 {
+        self.words.get(index).cloned().unwrap_or(0)
+    }
+
+    /// Gets the words up to and including the last non-zero one
+    fn used_words(&self) -> &[u64] {
+        let used = self.words.iter().rposition(|&word| word != 0).map_or(0, |index| index + 1);
+        &self.words[..used]
+    }
+
+    /// Makes sure there are at least X words
+    fn grow_to(&mut self, words: usize) {
+        if self.words.len() < words {
+            self.words.resize(words, 0);
+        }
+    }
+
+}
+
+impl BitInformation for BitSet {
+
+    /// Gets the number of members this BitSet can hold without growing
+    fn number_of_bits(&self) -> usize {
+        self.capacity()
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.contains(position)
+    }
+
+}
+
+impl BitIteration for BitSet {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_clear_bit(position)
+    }
+
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &BitSet) -> bool {
+        self.used_words() == other.used_words()
+    }
+}
+
+impl Eq for BitSet {}
+
+impl Hash for BitSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.used_words().hash(state);
+    }
+}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> BitOrAssign<&'a BitSet> for BitSet {
+    fn bitor_assign(&mut self, other: &'a BitSet) {
+        self.union_with(other);
+    }
+}
+
+impl<'a> BitAndAssign<&'a BitSet> for BitSet {
+    fn bitand_assign(&mut self, other: &'a BitSet) {
+        self.intersect_with(other);
+    }
+}
+
+impl<'a> BitXorAssign<&'a BitSet> for BitSet {
+    fn bitxor_assign(&mut self, other: &'a BitSet) {
+        self.symmetric_difference_with(other);
+    }
+}
+
+impl<'a> SubAssign<&'a BitSet> for BitSet {
+    fn sub_assign(&mut self, other: &'a BitSet) {
+        self.difference_with(other);
+    }
+}
+
+impl<'b> BitOr<&'b BitSet> for &BitSet {
+    type Output = BitSet;
+
+    fn bitor(self, other: &'b BitSet) -> BitSet {
+        self.union(other)
+    }
+}
+
+impl<'b> BitAnd<&'b BitSet> for &BitSet {
+    type Output = BitSet;
+
+    fn bitand(self, other: &'b BitSet) -> BitSet {
+        self.intersection(other)
+    }
+}
+
+impl<'b> BitXor<&'b BitSet> for &BitSet {
+    type Output = BitSet;
+
+    fn bitxor(self, other: &'b BitSet) -> BitSet {
+        self.symmetric_difference(other)
+    }
+}
+
+impl<'b> Sub<&'b BitSet> for &BitSet {
+    type Output = BitSet;
+
+    fn sub(self, other: &'b BitSet) -> BitSet {
+        self.difference(other)
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(values: I) -> BitSet {
+        let mut set = BitSet::new();
+        set.extend(values);
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the members of a BitSet in ascending order
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    words: &'a [u64],
+    position: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.position >= self.end {
+            return None;
+        }
+
+        match self.words.next_set_bit(self.position).filter(|&found| found < self.end) {
+            Some(found) => {
+                self.position = found + 1;
+                Some(found)
+            },
+            None => {
+                self.position = self.end;
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn set(values: &[usize]) -> BitSet {
+        values.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_insert_remove_contains() {
+        let mut ids = BitSet::new();
+
+        assert!(ids.insert(3));
+        assert!(ids.insert(200));
+        assert!(!ids.insert(3));
+        assert_eq!(2, ids.len());
+        assert_eq!(256, ids.capacity());
+        assert!(ids.contains(200));
+        assert!(!ids.contains(199));
+        assert!(!ids.contains(100_000));
+
+        assert!(ids.remove(200));
+        assert!(!ids.remove(200));
+        assert!(!ids.remove(100_000));
+        assert_eq!(vec![3], ids.iter().collect::<Vec<_>>());
+
+        ids.clear();
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn test_from_words() {
+        let ids = BitSet::from_words(&[0b1010, 0, 1]);
+
+        assert_eq!(192, ids.capacity());
+        assert_eq!(vec![1, 3, 128], ids.iter().collect::<Vec<_>>());
+        assert_eq!(set(&[1, 3, 128]), ids);
+    }
+
+    #[test]
+    fn test_min_max() {
+        assert_eq!(None, BitSet::new().min());
+        assert_eq!(None, BitSet::with_capacity(500).max());
+
+        let ids = set(&[70, 5, 191, 64]);
+        assert_eq!(Some(5), ids.min());
+        assert_eq!(Some(191), ids.max());
+    }
+
+    #[test]
+    fn test_ordered_iteration() {
+        let ids = set(&[130, 2, 64, 63, 0]);
+
+        assert_eq!(vec![0, 2, 63, 64, 130], ids.iter().collect::<Vec<_>>());
+        assert_eq!(vec![0, 2, 63, 64, 130], (&ids).into_iter().collect::<Vec<_>>());
+        assert_eq!(vec![0, 2, 63, 64, 130], ids.ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_range() {
+        let ids = set(&[1, 10, 63, 64, 65, 300]);
+
+        assert_eq!(vec![10, 63, 64], ids.range(10..65).collect::<Vec<_>>());
+        assert_eq!(vec![63, 64, 65], ids.range(11..=65).collect::<Vec<_>>());
+        assert_eq!(vec![65, 300], ids.range(65..).collect::<Vec<_>>());
+        assert_eq!(vec![1, 10], ids.range(..63).collect::<Vec<_>>());
+        assert_eq!(6, ids.range(..).count());
+        assert_eq!(0, ids.range(400..500).count());
+        assert_eq!(0, ids.range(usize::MAX..).count());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[1, 2, 3, 100]);
+        let b = set(&[2, 3, 4, 300]);
+
+        assert_eq!(set(&[1, 2, 3, 4, 100, 300]), a.union(&b));
+        assert_eq!(set(&[2, 3]), a.intersection(&b));
+        assert_eq!(set(&[1, 100]), a.difference(&b));
+        assert_eq!(set(&[4, 300]), b.difference(&a));
+        assert_eq!(set(&[1, 4, 100, 300]), a.symmetric_difference(&b));
+
+        assert_eq!(a.union(&b), &a | &b);
+        assert_eq!(a.intersection(&b), &a & &b);
+        assert_eq!(a.difference(&b), &a - &b);
+        assert_eq!(a.symmetric_difference(&b), &a ^ &b);
+    }
+
+    #[test]
+    fn test_set_operations_in_place() {
+        let b = set(&[2, 3, 4, 300]);
+
+        let mut a = set(&[1, 2, 3, 100]);
+        a |= &b;
+        assert_eq!(set(&[1, 2, 3, 4, 100, 300]), a);
+
+        let mut a = set(&[1, 2, 3, 100]);
+        a &= &b;
+        assert_eq!(set(&[2, 3]), a);
+
+        let mut a = set(&[1, 2, 3, 100]);
+        a -= &b;
+        assert_eq!(set(&[1, 100]), a);
+
+        let mut a = set(&[1, 2, 3, 100]);
+        a ^= &b;
+        assert_eq!(set(&[1, 4, 100, 300]), a);
+    }
+
+    #[test]
+    fn test_subset_superset_disjoint() {
+        let small = set(&[2, 70]);
+        let large = set(&[1, 2, 70, 500]);
+        let other = set(&[3, 71]);
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(large.is_superset(&small));
+        assert!(BitSet::new().is_subset(&small));
+        assert!(small.is_disjoint(&other));
+        assert!(!small.is_disjoint(&large));
+        assert!(BitSet::with_capacity(1000).is_subset(&BitSet::new()));
+    }
+
+    #[test]
+    fn test_equality_ignores_capacity() {
+        let mut a = BitSet::with_capacity(1000);
+        a.insert(5);
+        let mut b = set(&[5, 900]);
+        b.remove(900);
+
+        assert_eq!(a, b);
+        assert_eq!(BitSet::new(), BitSet::with_capacity(128));
+        assert!(set(&[1]) != set(&[1, 5]));
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!("{1, 64}", format!("{:?}", set(&[64, 1])));
+    }
+
+}
@@ -0,0 +1,568 @@
+//! A growable vector of bits, packed into u64 words
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not};
+use core::ops::{Shl, ShlAssign, Shr, ShrAssign};
+
+use words::{self, words_for_bits, WORD_BITS};
+use {BitInformation, BitIteration, BitManipulation, BitOrdering};
+
+/// A growable vector of bits
+///
+/// Bit X lives in bit `X % 64` of word `X / 64`. Any bits in the last word past the length are
+/// always zero.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+
+    /// Creates an empty BitVec
+    pub fn new() -> BitVec {
+        BitVec { words: Vec::new(), len: 0 }
+    }
+
+    /// Creates an empty BitVec with room for at least `capacity` bits
+    pub fn with_capacity(capacity: usize) -> BitVec {
+        BitVec { words: Vec::with_capacity(words_for_bits(capacity)), len: 0 }
+    }
+
+    /// Creates a BitVec of `len` bits that are all set to `value`
+    pub fn from_elem(len: usize, value: bool) -> BitVec {
+        let mut vec = BitVec::new();
+        vec.resize(len, value);
+        vec
+    }
+
+    /// Creates a BitVec holding every bit of some words
+    pub fn from_words(words: &[u64]) -> BitVec {
+        BitVec { words: words.to_vec(), len: words.len() * WORD_BITS }
+    }
+
+    /// Gets the words backing this BitVec
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Gets the number of bits in this BitVec
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if this BitVec has no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets bit X, or None if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(self.words.has_x_bit(index))
+        } else {
+            None
+        }
+    }
+
+    /// Sets bit X to `value`, panicking if it is out of bounds
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "bit index {} is out of range for length {}", index, self.len);
+        self.words.assign_x_bit(index, value);
+    }
+
+    /// Adds a bit to the end
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.words.assign_x_bit(self.len - 1, value);
+    }
+
+    /// Removes the last bit, or returns None if this BitVec is empty
+    pub fn pop(&mut self) -> Option<bool> {
+        let value = self.get(self.len.checked_sub(1)?)?;
+        self.truncate(self.len - 1);
+        Some(value)
+    }
+
+    /// Inserts a bit at position X, shifting every later bit up by one
+    pub fn insert(&mut self, index: usize, value: bool) {
+        assert!(index <= self.len, "insertion index {} is out of range for length {}", index, self.len);
+
+        self.push(false);
+
+        let first = index / WORD_BITS;
+        for word in (first + 1..self.words.len()).rev() {
+            self.words[word] = (self.words[word] << 1) | (self.words[word - 1] >> (WORD_BITS - 1));
+        }
+
+        let low_mask = (1u64 << (index % WORD_BITS)) - 1;
+        let word = self.words[first];
+        self.words[first] = (word & low_mask) | ((word & !low_mask) << 1);
+
+        self.words.assign_x_bit(index, value);
+    }
+
+    /// Removes the bit at position X, shifting every later bit down by one
+    pub fn remove(&mut self, index: usize) -> bool {
+        assert!(index < self.len, "removal index {} is out of range for length {}", index, self.len);
+
+        let value = self.words.has_x_bit(index);
+
+        let first = index / WORD_BITS;
+        let low_mask = (1u64 << (index % WORD_BITS)) - 1;
+        let word = self.words[first];
+        self.words[first] = (word & low_mask) | ((word >> 1) & !low_mask);
+
+        for word in first..self.words.len() - 1 {
+            self.words[word] |= (self.words[word + 1] & 1) << (WORD_BITS - 1);
+            self.words[word + 1] >>= 1;
+        }
+
+        self.truncate(self.len - 1);
+        value
+    }
+
+    /// Shortens this BitVec to `len` bits, doing nothing if it is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.words.truncate(words_for_bits(len));
+            self.len = len;
+            self.clear_unused_bits();
+        }
+    }
+
+    /// Grows or shrinks this BitVec to `len` bits, filling any new bits with `value`
+    pub fn resize(&mut self, len: usize, value: bool) {
+        if len <= self.len {
+            self.truncate(len);
+        } else {
+            let old_len = self.len;
+            self.words.resize(words_for_bits(len), 0);
+            self.len = len;
+            self.fill(old_len, len, value);
+        }
+    }
+
+    /// Removes every bit
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    /// Sets every bit from `start` up to (but not including) `end` to `value`
+    pub fn fill(&mut self, start: usize, end: usize, value: bool) {
+        assert!(start <= end && end <= self.len, "bit range {}..{} is out of range for length {}", start, end, self.len);
+        words::fill(&mut self.words, start, end, value);
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Gets the number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Zeroes the bits of the last word that are past the length
+    fn clear_unused_bits(&mut self) {
+        words::clear_unused_bits(&mut self.words, self.len);
+    }
+
+    /// Combines every word with the matching word of another BitVec of the same length
+    fn combine<F: Fn(u64, u64) -> u64>(&mut self, other: &BitVec, operation: F) {
+        assert_eq!(self.len, other.len, "BitVecs of length {} and {} can not be combined", self.len, other.len);
+
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word = operation(*word, *other);
+        }
+    }
+
+}
+
+impl BitInformation for BitVec {
+
+    /// Gets the number of bits contained in this BitVec
+    fn number_of_bits(&self) -> usize {
+        self.len
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.get(position).unwrap_or(false)
+    }
+
+}
+
+impl BitManipulation for BitVec {
+
+    fn set_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.words.set_x_bit(position);
+        }
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.words.clear_x_bit(position);
+        }
+    }
+
+    fn toggle_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.words.toggle_x_bit(position);
+        }
+    }
+
+}
+
+impl BitIteration for BitVec {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_clear_bit(position).filter(|&found| found < self.len)
+    }
+
+}
+
+impl BitOrdering for BitVec {}
+
+impl Index<usize> for BitVec {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &bool {
+        match self.get(index) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!("bit index {} is out of range for length {}", index, self.len),
+        }
+    }
+}
+
+impl<'a> BitAndAssign<&'a BitVec> for BitVec {
+    fn bitand_assign(&mut self, other: &'a BitVec) {
+        self.combine(other, |ours, theirs| ours & theirs);
+    }
+}
+
+impl<'a> BitOrAssign<&'a BitVec> for BitVec {
+    fn bitor_assign(&mut self, other: &'a BitVec) {
+        self.combine(other, |ours, theirs| ours | theirs);
+    }
+}
+
+impl<'a> BitXorAssign<&'a BitVec> for BitVec {
+    fn bitxor_assign(&mut self, other: &'a BitVec) {
+        self.combine(other, |ours, theirs| ours ^ theirs);
+    }
+}
+
+impl<'b> BitAnd<&'b BitVec> for &BitVec {
+    type Output = BitVec;
+
+    fn bitand(self, other: &'b BitVec) -> BitVec {
+        let mut result = self.clone();
+        result &= other;
+        result
+    }
+}
+
+impl<'b> BitOr<&'b BitVec> for &BitVec {
+    type Output = BitVec;
+
+    fn bitor(self, other: &'b BitVec) -> BitVec {
+        let mut result = self.clone();
+        result |= other;
+        result
+    }
+}
+
+impl<'b> BitXor<&'b BitVec> for &BitVec {
+    type Output = BitVec;
+
+    fn bitxor(self, other: &'b BitVec) -> BitVec {
+        let mut result = self.clone();
+        result ^= other;
+        result
+    }
+}
+
+impl Not for BitVec {
+    type Output = BitVec;
+
+    fn not(mut self) -> BitVec {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+        self.clear_unused_bits();
+        self
+    }
+}
+
+impl Not for &BitVec {
+    type Output = BitVec;
+
+    fn not(self) -> BitVec {
+        !self.clone()
+    }
+}
+
+impl ShlAssign<usize> for BitVec {
+
+    /// Moves bit X up to bit X + `amount`, dropping the bits that move past the length
+    fn shl_assign(&mut self, amount: usize) {
+        words::shift_up(&mut self.words, amount);
+        self.clear_unused_bits();
+    }
+
+}
+
+impl ShrAssign<usize> for BitVec {
+
+    /// Moves bit X down to bit X - `amount`, dropping the bits that move below bit 0
+    fn shr_assign(&mut self, amount: usize) {
+        words::shift_down(&mut self.words, amount);
+    }
+
+}
+
+impl Shl<usize> for BitVec {
+    type Output = BitVec;
+
+    fn shl(mut self, amount: usize) -> BitVec {
+        self <<= amount;
+        self
+    }
+}
+
+impl Shl<usize> for &BitVec {
+    type Output = BitVec;
+
+    fn shl(self, amount: usize) -> BitVec {
+        self.clone() << amount
+    }
+}
+
+impl Shr<usize> for BitVec {
+    type Output = BitVec;
+
+    fn shr(mut self, amount: usize) -> BitVec {
+        self >>= amount;
+        self
+    }
+}
+
+impl Shr<usize> for &BitVec {
+    type Output = BitVec;
+
+    fn shr(self, amount: usize) -> BitVec {
+        self.clone() >> amount
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BitVec[")?;
+        for bit in self.bits() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, bits: I) {
+        for bit in bits {
+            self.push(bit);
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(bits: I) -> BitVec {
+        let mut vec = BitVec::new();
+        vec.extend(bits);
+        vec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn bit_vec(bits: &str) -> BitVec {
+        bits.chars().map(|bit| bit == '1').collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut vec = BitVec::new();
+        for index in 0..130 {
+            vec.push(index % 3 == 0);
+        }
+
+        assert_eq!(130, vec.len());
+        assert_eq!(3, vec.as_words().len());
+        assert!(vec[129]);
+        assert!(!vec[128]);
+        assert_eq!(Some(true), vec.pop());
+        assert_eq!(Some(false), vec.pop());
+        assert_eq!(128, vec.len());
+        assert_eq!(2, vec.as_words().len());
+
+        vec.clear();
+        assert_eq!(None, vec.pop());
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut vec = BitVec::from_elem(70, false);
+        vec.set(69, true);
+        vec.set(3, true);
+
+        assert_eq!(Some(true), vec.get(69));
+        assert_eq!(None, vec.get(70));
+        assert_eq!(2, vec.count_ones());
+        assert_eq!(68, vec.count_zeros());
+        assert_eq!(&[8, 32], vec.as_words());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_out_of_range() {
+        BitVec::from_elem(10, false).set(10, true);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut vec = bit_vec("1011");
+        vec.insert(1, true);
+        assert_eq!(bit_vec("11011"), vec);
+        vec.insert(5, false);
+        assert_eq!(bit_vec("110110"), vec);
+        assert!(vec.remove(0));
+        assert_eq!(bit_vec("10110"), vec);
+        assert!(!vec.remove(4));
+        assert_eq!(bit_vec("1011"), vec);
+    }
+
+    #[test]
+    fn test_insert_remove_across_words() {
+        let mut vec = BitVec::from_elem(128, false);
+        vec.set(63, true);
+        vec.set(127, true);
+
+        vec.insert(0, true);
+        assert_eq!(129, vec.len());
+        assert_eq!(vec![0, 64, 128], vec.ones().collect::<Vec<_>>());
+
+        assert!(vec.remove(0));
+        assert!(vec.remove(63));
+        assert_eq!(127, vec.len());
+        assert_eq!(vec![126], vec.ones().collect::<Vec<_>>());
+        assert_eq!(2, vec.as_words().len());
+    }
+
+    #[test]
+    fn test_truncate_resize() {
+        let mut vec = BitVec::from_elem(100, true);
+
+        vec.truncate(65);
+        assert_eq!(65, vec.count_ones());
+        assert_eq!(&[u64::MAX, 1], vec.as_words());
+
+        vec.resize(70, false);
+        assert_eq!(65, vec.count_ones());
+        vec.resize(200, true);
+        assert_eq!(195, vec.count_ones());
+        assert_eq!(Some(65), vec.next_clear_bit(0));
+        vec.resize(3, true);
+        assert_eq!(bit_vec("111"), vec);
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut vec = BitVec::from_elem(150, false);
+        vec.fill(60, 140, true);
+
+        assert_eq!(80, vec.count_ones());
+        assert_eq!(Some(60), vec.next_set_bit(0));
+        assert_eq!(Some(140), vec.next_clear_bit(60));
+
+        vec.fill(64, 128, false);
+        assert_eq!(16, vec.count_ones());
+    }
+
+    #[test]
+    fn test_bulk_operations() {
+        let left = bit_vec("1100110011");
+        let right = bit_vec("1010101010");
+
+        assert_eq!(bit_vec("1000100010"), &left & &right);
+        assert_eq!(bit_vec("1110111011"), &left | &right);
+        assert_eq!(bit_vec("0110011001"), &left ^ &right);
+        assert_eq!(bit_vec("0011001100"), !&left);
+        assert_eq!(4, (!left.clone()).count_ones());
+
+        let mut combined = left.clone();
+        combined ^= &left;
+        assert_eq!(0, combined.count_ones());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bulk_operation_length_mismatch() {
+        let _ = &bit_vec("101") & &bit_vec("10");
+    }
+
+    #[test]
+    fn test_shift_operators() {
+        let bits = bit_vec("1100110011");
+
+        assert_eq!(bit_vec("0001100110"), &bits << 3);
+        assert_eq!(bit_vec("0011001100"), &bits >> 2);
+        assert_eq!(BitVec::new(), BitVec::new() << 5);
+        assert_eq!(0, (bits.clone() << 10).count_ones());
+
+        let mut long: BitVec = (0..130).map(|bit| bit % 3 == 0).collect();
+        long <<= 65;
+        assert_eq!((65..130).step_by(3).collect::<Vec<_>>(), long.ones().collect::<Vec<_>>());
+        long >>= 66;
+        assert_eq!((2..64).step_by(3).collect::<Vec<_>>(), long.ones().collect::<Vec<_>>());
+        assert_eq!(130, long.len());
+    }
+
+    #[test]
+    fn test_bit_traits() {
+        let mut vec = bit_vec("0010");
+
+        assert_eq!(4, vec.number_of_bits());
+        assert!(vec.has_x_bit(2));
+        assert!(!vec.has_x_bit(4));
+        vec.set_x_bit(0);
+        vec.toggle_x_bit(2);
+        vec.set_x_bit(4);
+        assert_eq!(bit_vec("1000"), vec);
+        assert_eq!(vec![1, 2, 3], vec.zeros().collect::<Vec<_>>());
+        assert_eq!(vec![(0, 1, true), (1, 3, false)], vec.runs().collect::<Vec<_>>());
+        assert_eq!("BitVec[1000]", format!("{:?}", vec));
+    }
+
+    #[test]
+    fn test_most_signifigant_bit() {
+        assert!(!BitVec::new().has_most_signifigant_bit());
+        assert!(!bit_vec("10").has_most_signifigant_bit());
+        assert!(bit_vec("01").has_most_signifigant_bit());
+    }
+
+}
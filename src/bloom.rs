@@ -0,0 +1,525 @@
+//! Bloom filters, which test whether an item might be in a set using a fixed number of bits
+//!
+//! Inserting an item sets `hashes` bits chosen by hashing it, and an item might be in the set only
+//! if all of its bits are set. There are no false negatives, and the false positive rate depends
+//! on how full the filter is.
+//!
+//! Items are hashed with 64-bit FNV-1a rather than the randomly keyed hasher std uses, so the same
+//! item sets the same bits every time a program runs. The bits for each item are picked with double
+//! hashing, from two values mixed out of that one hash.
+//!
+//! Items are fed to the hasher through `core::hash::Hash`, which is not stable across targets or
+//! Rust versions. Integers are written as native-endian bytes, `usize` changes width, and the
+//! length prefix written for strings and slices may change. A filter saved with `as_bit_vec` should
+//! only be loaded with `from_bit_vec` by the same build on the same target.
+
+use alloc::vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use {BitError, BitVec, Nibble, NibbleVec};
+
+/// The natural logarithm of 2
+const LN_2: f64 = core::f64::consts::LN_2;
+
+/// A 64-bit FNV-1a hasher
+struct Fnv(u64);
+
+impl Fnv {
+
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Spreads the bits of a hash out, so that every input bit affects every output bit
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Gets the `hashes` positions out of `slots` that an item maps to
+fn probes<T: Hash + ?Sized>(item: &T, slots: usize, hashes: usize) -> impl Iterator<Item = usize> {
+    let mut hasher = Fnv::new();
+    item.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let first = mix(hash);
+    let step = mix(hash ^ 0x9e37_79b9_7f4a_7c15) | 1;
+
+    (0..hashes as u64).map(move |index| (first.wrapping_add(index.wrapping_mul(step)) % slots as u64) as usize)
+}
+
+/// Gets the natural logarithm of a positive, normal float
+///
+/// core has no `ln`, so this splits X into `m * 2^e` with `m` in `1..2` and sums the series for
+/// `ln(m) = 2 * atanh((m - 1) / (m + 1))`.
+fn ln(value: f64) -> f64 {
+    let bits = value.to_bits();
+    let exponent = ((bits >> 52) & 0x7FF) as i64 - 1023;
+    let mantissa = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+
+    let ratio = (mantissa - 1.0) / (mantissa + 1.0);
+    let square = ratio * ratio;
+    let mut term = ratio;
+    let mut sum = 0.0;
+    for power in 0..24 {
+        sum += term / (2 * power + 1) as f64;
+        term *= square;
+    }
+
+    exponent as f64 * LN_2 + 2.0 * sum
+}
+
+/// Rounds a positive float up to a whole number
+fn ceil(value: f64) -> usize {
+    let whole = value as usize;
+    if (whole as f64) < value { whole + 1 } else { whole }
+}
+
+/// Works out how many bits and hash functions a filter needs to hold `items` items with a false
+/// positive rate of at most `rate`
+fn optimal_size(items: usize, rate: f64) -> (usize, usize) {
+    assert!(rate > 0.0 && rate < 1.0, "false positive rate {} must be between 0 and 1", rate);
+
+    let items = items.max(1) as f64;
+    let bits = ceil(-items * ln(rate.max(f64::MIN_POSITIVE)) / (LN_2 * LN_2)).max(1);
+    let hashes = ((bits as f64 / items * LN_2 + 0.5) as usize).max(1);
+    (bits, hashes)
+}
+
+/// Panics if a filter would have no bits or no hash functions
+fn check_size(slots: usize, hashes: usize) {
+    assert!(slots > 0, "a Bloom filter needs at least one bit");
+    assert!(hashes > 0, "a Bloom filter needs at least one hash function");
+}
+
+/// A Bloom filter backed by a BitVec
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BloomFilter {
+    bits: BitVec,
+    hashes: usize,
+}
+
+impl BloomFilter {
+
+    /// Creates an empty BloomFilter with X bits and `hashes` hash functions, panicking if either
+    /// is zero
+    pub fn new(bits: usize, hashes: usize) -> BloomFilter {
+        check_size(bits, hashes);
+        BloomFilter { bits: BitVec::from_elem(bits, false), hashes }
+    }
+
+    /// Creates an empty BloomFilter sized to hold `items` items with a false positive rate of at
+    /// most `rate`, panicking if the rate is not between 0 and 1
+    pub fn with_false_positive_rate(items: usize, rate: f64) -> BloomFilter {
+        let (bits, hashes) = optimal_size(items, rate);
+        BloomFilter::new(bits, hashes)
+    }
+
+    /// Rebuilds a BloomFilter from its bits, such as ones saved from `as_bit_vec` by the same build
+    /// on the same target
+    pub fn from_bit_vec(bits: BitVec, hashes: usize) -> BloomFilter {
+        check_size(bits.len(), hashes);
+        BloomFilter { bits, hashes }
+    }
+
+    /// Gets the bits backing this filter
+    pub fn as_bit_vec(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Gets the number of bits in this filter
+    pub fn bit_count(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Gets the number of bits set for each item
+    pub fn hash_count(&self) -> usize {
+        self.hashes
+    }
+
+    /// Adds an item, returning false if it might already have been in the filter
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        let mut added = false;
+        for position in probes(item, self.bits.len(), self.hashes) {
+            if self.bits.get(position) == Some(false) {
+                self.bits.set(position, true);
+                added = true;
+            }
+        }
+        added
+    }
+
+    /// Checks to see if an item might be in the filter
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        probes(item, self.bits.len(), self.hashes).all(|position| self.bits.get(position) == Some(true))
+    }
+
+    /// Removes every item
+    pub fn clear(&mut self) {
+        let len = self.bits.len();
+        self.bits.fill(0, len, false);
+    }
+
+    /// Checks to see if no items have been added
+    pub fn is_clear(&self) -> bool {
+        self.bits.count_ones() == 0
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    /// Estimates the chance that an item that was never added is reported as present, from how
+    /// many bits are set
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let fill = self.count_ones() as f64 / self.bits.len() as f64;
+        (0..self.hashes).fold(1.0, |rate, _| rate * fill)
+    }
+
+    /// Gets a new filter holding every item of both filters, or an error if they have different
+    /// sizes or numbers of hash functions
+    pub fn union(&self, other: &BloomFilter) -> Result<BloomFilter, BitError> {
+        let mut result = self.clone();
+        result.union_with(other)?;
+        Ok(result)
+    }
+
+    /// Gets a new filter that might hold the items both filters hold, or an error if they have
+    /// different sizes or numbers of hash functions
+    ///
+    /// The result can report items that were only in one filter, so it has a higher false positive
+    /// rate than a filter built from the shared items directly.
+    pub fn intersection(&self, other: &BloomFilter) -> Result<BloomFilter, BitError> {
+        let mut result = self.clone();
+        result.intersect_with(other)?;
+        Ok(result)
+    }
+
+    /// Adds every item of the other filter to this one, or returns an error if they have different
+    /// sizes or numbers of hash functions
+    pub fn union_with(&mut self, other: &BloomFilter) -> Result<(), BitError> {
+        self.check_compatible(other)?;
+        self.bits |= &other.bits;
+        Ok(())
+    }
+
+    /// Keeps only the bits both filters have set, or returns an error if they have different sizes
+    /// or numbers of hash functions
+    pub fn intersect_with(&mut self, other: &BloomFilter) -> Result<(), BitError> {
+        self.check_compatible(other)?;
+        self.bits &= &other.bits;
+        Ok(())
+    }
+
+    /// Checks to see if the other filter hashes items to the same bits as this one
+    pub fn is_compatible(&self, other: &BloomFilter) -> bool {
+        self.bits.len() == other.bits.len() && self.hashes == other.hashes
+    }
+
+    fn check_compatible(&self, other: &BloomFilter) -> Result<(), BitError> {
+        if self.is_compatible(other) {
+            Ok(())
+        } else {
+            Err(BitError::IncompatibleFilters)
+        }
+    }
+
+}
+
+impl fmt::Debug for BloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BloomFilter {{ bits: {}, hashes: {}, ones: {} }}", self.bits.len(), self.hashes, self.count_ones())
+    }
+}
+
+/// A Bloom filter with a 4-bit counter in place of each bit, so that items can be removed
+///
+/// Counters stop at 15 and are never decremented after that, since they no longer know how many
+/// items set them, and they never go below zero. Removing an item that was never added can make
+/// other items go missing.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CountingBloomFilter {
+    counters: NibbleVec,
+    hashes: usize,
+}
+
+impl CountingBloomFilter {
+
+    /// Creates an empty CountingBloomFilter with X counters and `hashes` hash functions, panicking
+    /// if either is zero
+    pub fn new(counters: usize, hashes: usize) -> CountingBloomFilter {
+        check_size(counters, hashes);
+
+        let mut nibbles = NibbleVec::from_bytes(&vec![0; counters.div_ceil(2)]);
+        nibbles.truncate(counters);
+        CountingBloomFilter { counters: nibbles, hashes }
+    }
+
+    /// Creates an empty CountingBloomFilter sized to hold `items` items with a false positive rate
+    /// of at most `rate`, panicking if the rate is not between 0 and 1
+    pub fn with_false_positive_rate(items: usize, rate: f64) -> CountingBloomFilter {
+        let (counters, hashes) = optimal_size(items, rate);
+        CountingBloomFilter::new(counters, hashes)
+    }
+
+    /// Gets the counters backing this filter
+    pub fn as_nibble_vec(&self) -> &NibbleVec {
+        &self.counters
+    }
+
+    /// Gets the number of counters in this filter
+    pub fn counter_count(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Gets the number of counters incremented for each item
+    pub fn hash_count(&self) -> usize {
+        self.hashes
+    }
+
+    /// Adds an item
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for position in probes(item, self.counters.len(), self.hashes) {
+            let counter = self.counters[position];
+            if counter != Nibble::MAX {
+                self.counters.set(position, counter + Nibble::from_low_bits(1));
+            }
+        }
+    }
+
+    /// Removes an item, returning false and leaving the filter alone if it was not in the filter
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+
+        for position in probes(item, self.counters.len(), self.hashes) {
+            let counter = self.counters[position];
+            if counter != Nibble::MAX && counter != Nibble::MIN {
+                self.counters.set(position, counter - Nibble::from_low_bits(1));
+            }
+        }
+        true
+    }
+
+    /// Checks to see if an item might be in the filter
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.count(item) > 0
+    }
+
+    /// Estimates how many times an item has been added, which is never less than the real count
+    /// unless other items have been removed that were never added
+    pub fn count<T: Hash + ?Sized>(&self, item: &T) -> u8 {
+        probes(item, self.counters.len(), self.hashes)
+            .map(|position| self.counters[position].value())
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Removes every item
+    pub fn clear(&mut self) {
+        let len = self.counters.len();
+        *self = CountingBloomFilter::new(len, self.hashes);
+    }
+
+    /// Gets a plain BloomFilter with a bit set for every counter above zero
+    pub fn to_bloom_filter(&self) -> BloomFilter {
+        let bits = self.counters.iter().map(|counter| counter != Nibble::MIN).collect();
+        BloomFilter { bits, hashes: self.hashes }
+    }
+
+}
+
+impl fmt::Debug for CountingBloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CountingBloomFilter {{ counters: {}, hashes: {} }}", self.counters.len(), self.hashes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut filter = BloomFilter::with_false_positive_rate(1000, 0.01);
+        for key in 0..1000u64 {
+            filter.insert(&key);
+        }
+
+        assert!((0..1000u64).all(|key| filter.contains(&key)));
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = BloomFilter::with_false_positive_rate(1000, 0.01);
+        assert_eq!(9586, filter.bit_count());
+        assert_eq!(7, filter.hash_count());
+
+        for key in 0..1000u64 {
+            filter.insert(&key);
+        }
+
+        let false_positives = (1000..101_000u64).filter(|key| filter.contains(key)).count();
+        assert!(false_positives < 1500, "{} false positives", false_positives);
+        assert!(filter.estimated_false_positive_rate() < 0.015);
+    }
+
+    #[test]
+    fn test_insert_and_clear() {
+        let mut filter = BloomFilter::new(256, 3);
+        assert!(filter.is_clear());
+        assert!(!filter.contains("disk"));
+
+        assert!(filter.insert("disk"));
+        assert!(!filter.insert("disk"));
+        assert!(filter.contains("disk"));
+        assert!(filter.count_ones() <= 3);
+
+        filter.clear();
+        assert!(filter.is_clear());
+        assert!(!filter.contains("disk"));
+        assert_eq!(256, filter.bit_count());
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let mut left = BloomFilter::new(1024, 4);
+        let mut right = BloomFilter::new(1024, 4);
+        for key in 0..20u32 {
+            left.insert(&key);
+        }
+        for key in 10..30u32 {
+            right.insert(&key);
+        }
+
+        let union = left.union(&right).unwrap();
+        assert!((0..30u32).all(|key| union.contains(&key)));
+
+        let intersection = left.intersection(&right).unwrap();
+        assert!((10..20u32).all(|key| intersection.contains(&key)));
+        assert!(intersection.count_ones() <= left.count_ones().min(right.count_ones()));
+
+        let other = BloomFilter::new(1024, 5);
+        assert!(!left.is_compatible(&other));
+        assert_eq!(Err(BitError::IncompatibleFilters), left.union(&other));
+        assert_eq!(Err(BitError::IncompatibleFilters), left.intersect_with(&BloomFilter::new(512, 4)));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut filter = BloomFilter::new(100, 2);
+        filter.insert(&42u64);
+
+        let restored = BloomFilter::from_bit_vec(filter.as_bit_vec().clone(), 2);
+        assert_eq!(filter, restored);
+        assert!(restored.contains(&42u64));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_hashes() {
+        BloomFilter::new(100, 0);
+    }
+
+    #[test]
+    fn test_ln() {
+        for &(value, expected) in &[(1.0, 0.0), (2.0, LN_2), (0.01, -4.605_170_185_988_091), (1e300, 690.775_527_898_213_7)] {
+            assert!((ln(value) - expected).abs() < 1e-9, "ln({}) = {}", value, ln(value));
+        }
+    }
+
+    #[test]
+    fn test_counting_insert_and_remove() {
+        let mut filter = CountingBloomFilter::with_false_positive_rate(100, 0.01);
+        for key in 0..100u64 {
+            filter.insert(&key);
+        }
+        assert!((0..100u64).all(|key| filter.contains(&key)));
+
+        for key in 0..50u64 {
+            assert!(filter.remove(&key));
+        }
+        assert!((50..100u64).all(|key| filter.contains(&key)));
+        assert!((0..50u64).filter(|key| filter.contains(key)).count() < 5);
+
+        let mut empty = CountingBloomFilter::new(64, 3);
+        assert!(!empty.remove("missing"));
+        assert_eq!(0, empty.count("missing"));
+    }
+
+    #[test]
+    fn test_counting_remove_stops_at_zero() {
+        // One counter probed twice per item, holding 1 as if a single probe had landed on it, so
+        // removing any item is a false positive that decrements it twice
+        let mut counters = NibbleVec::new();
+        counters.push(Nibble::from_low_bits(1));
+        let mut filter = CountingBloomFilter { counters, hashes: 2 };
+
+        assert!(filter.remove("never added"));
+        assert_eq!(0, filter.count("never added"));
+        assert!(!filter.remove("never added"));
+    }
+
+    #[test]
+    fn test_counting_saturates() {
+        let mut filter = CountingBloomFilter::new(16, 2);
+        for _ in 0..20 {
+            filter.insert("hot");
+        }
+        assert_eq!(15, filter.count("hot"));
+
+        for _ in 0..20 {
+            filter.remove("hot");
+        }
+        assert_eq!(15, filter.count("hot"));
+
+        filter.clear();
+        assert_eq!(0, filter.count("hot"));
+        assert_eq!(16, filter.counter_count());
+    }
+
+    #[test]
+    fn test_counting_to_bloom_filter() {
+        let mut counting = CountingBloomFilter::new(333, 4);
+        let mut plain = BloomFilter::new(333, 4);
+        for key in &["a", "b", "c"] {
+            counting.insert(key);
+            plain.insert(key);
+        }
+
+        assert_eq!(plain, counting.to_bloom_filter());
+        assert_eq!(333, counting.as_nibble_vec().len());
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut filter = BloomFilter::new(64, 2);
+        filter.insert(&1u8);
+        let expected = format!("BloomFilter {{ bits: 64, hashes: 2, ones: {} }}", filter.count_ones());
+        assert_eq!(expected, format!("{:?}", filter));
+        assert_eq!("CountingBloomFilter { counters: 8, hashes: 3 }", format!("{:?}", CountingBloomFilter::new(8, 3)));
+    }
+
+}
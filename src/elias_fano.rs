@@ -0,0 +1,266 @@
+//! A compact encoding for sorted sequences of integers
+//!
+//! Each value is split into low bits and high bits. The low `low_width` bits of every value are
+//! packed one after another, and the high bits of value X are written in unary as bit
+//! `(value >> low_width) + X` of a `RankSelect`. That takes at most `2 + log2(max / len)` bits per
+//! value, and `select1` on the high bits gives random access.
+
+use core::fmt;
+
+use {BitIteration, BitQueries, BitVec, RankSelect};
+
+/// A sorted sequence of u64s in Elias-Fano encoding
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct EliasFano {
+    high: RankSelect,
+    low: BitVec,
+    low_width: usize,
+    len: usize,
+}
+
+impl EliasFano {
+
+    /// Encodes a sequence of values, returning None if they are not sorted in ascending order
+    pub fn from_sorted(values: &[u64]) -> Option<EliasFano> {
+        if values.windows(2).any(|pair| pair[0] > pair[1]) {
+            return None;
+        }
+
+        let len = values.len();
+        let max = values.last().cloned().unwrap_or(0);
+        let low_width = if len == 0 { 0 } else { (max / len as u64).floor_log2().unwrap_or(0) };
+
+        let mut high = BitVec::from_elem(len + (max >> low_width) as usize + 1, false);
+        let mut low = BitVec::with_capacity(len * low_width);
+
+        for (index, &value) in values.iter().enumerate() {
+            high.set((value >> low_width) as usize + index, true);
+            for position in 0..low_width {
+                low.push((value >> position) & 1 == 1);
+            }
+        }
+
+        Some(EliasFano { high: RankSelect::from(high), low, low_width, len })
+    }
+
+    /// Gets the number of values
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if there are no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets value X, or None if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index < self.len {
+            let high = self.high.select1(index)? - index;
+            Some(self.value_at(index, high))
+        } else {
+            None
+        }
+    }
+
+    /// Finds the first value that is at least X, along with its index
+    pub fn next_geq(&self, value: u64) -> Option<(usize, u64)> {
+        let bucket = (value >> self.low_width) as usize;
+        let start = if bucket == 0 {
+            0
+        } else {
+            self.high.select0(bucket - 1)? + 1
+        };
+
+        let mut index = start - bucket;
+        let mut position = start;
+        while index < self.len {
+            position = self.high.next_set_bit(position)?;
+            let found = self.value_at(index, position - index);
+            if found >= value {
+                return Some((index, found));
+            }
+
+            index += 1;
+            position += 1;
+        }
+
+        None
+    }
+
+    /// Checks to see if X is one of the values
+    pub fn contains(&self, value: u64) -> bool {
+        self.next_geq(value).is_some_and(|(_, found)| found == value)
+    }
+
+    /// Iterates over every value in ascending order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { sequence: self, index: 0, position: 0 }
+    }
+
+    /// Gets the number of bits used to store the values
+    pub fn size_in_bits(&self) -> usize {
+        self.high.len() + self.low.len()
+    }
+
+    /// Puts value X back together from its high bits and stored low bits
+    fn value_at(&self, index: usize, high: usize) -> u64 {
+        ((high as u64) << self.low_width) | self.low_bits(index)
+    }
+
+    /// Gets the stored low bits of value X
+    fn low_bits(&self, index: usize) -> u64 {
+        if self.low_width == 0 {
+            return 0;
+        }
+
+        let words = self.low.as_words();
+        let start = index * self.low_width;
+        let (word, offset) = (start / 64, start % 64);
+
+        let mut bits = words[word] >> offset;
+        if offset + self.low_width > 64 {
+            bits |= words[word + 1] << (64 - offset);
+        }
+
+        bits & ((1u64 << self.low_width) - 1)
+    }
+
+}
+
+impl fmt::Debug for EliasFano {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a EliasFano {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of an EliasFano sequence
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    sequence: &'a EliasFano,
+    index: usize,
+    position: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.index >= self.sequence.len {
+            return None;
+        }
+
+        let position = self.sequence.high.next_set_bit(self.position)?;
+        let value = self.sequence.value_at(self.index, position - self.index);
+
+        self.index += 1;
+        self.position = position + 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.sequence.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn postings() -> Vec<u64> {
+        vec![3, 4, 7, 13, 14, 15, 21, 25, 36, 38, 54, 62]
+    }
+
+    #[test]
+    fn test_get() {
+        let values = postings();
+        let sequence = EliasFano::from_sorted(&values).unwrap();
+
+        assert_eq!(values.len(), sequence.len());
+        for (index, &value) in values.iter().enumerate() {
+            assert_eq!(Some(value), sequence.get(index));
+        }
+        assert_eq!(None, sequence.get(values.len()));
+    }
+
+    #[test]
+    fn test_iter() {
+        let values = postings();
+        let sequence = EliasFano::from_sorted(&values).unwrap();
+
+        assert_eq!(values, sequence.iter().collect::<Vec<_>>());
+        assert_eq!(values.len(), sequence.iter().len());
+        assert_eq!(values, (&sequence).into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_next_geq() {
+        let sequence = EliasFano::from_sorted(&postings()).unwrap();
+
+        assert_eq!(Some((0, 3)), sequence.next_geq(0));
+        assert_eq!(Some((0, 3)), sequence.next_geq(3));
+        assert_eq!(Some((2, 7)), sequence.next_geq(5));
+        assert_eq!(Some((6, 21)), sequence.next_geq(16));
+        assert_eq!(Some((11, 62)), sequence.next_geq(55));
+        assert_eq!(None, sequence.next_geq(63));
+        assert_eq!(None, sequence.next_geq(u64::MAX));
+        assert!(sequence.contains(36));
+        assert!(!sequence.contains(37));
+    }
+
+    #[test]
+    fn test_duplicates_and_zero() {
+        let values = vec![0, 0, 5, 5, 5, 1000];
+        let sequence = EliasFano::from_sorted(&values).unwrap();
+
+        assert_eq!(values, sequence.iter().collect::<Vec<_>>());
+        assert_eq!(Some((2, 5)), sequence.next_geq(1));
+        assert_eq!(Some((0, 0)), sequence.next_geq(0));
+    }
+
+    #[test]
+    fn test_large_values() {
+        let values: Vec<u64> = (0..1000u64).map(|value| value * 1_000_003 + (value % 7)).collect();
+        let sequence = EliasFano::from_sorted(&values).unwrap();
+
+        assert_eq!(values, sequence.iter().collect::<Vec<_>>());
+        assert_eq!(Some(values[500]), sequence.get(500));
+        assert_eq!(Some((501, values[501])), sequence.next_geq(values[500] + 1));
+        assert!(sequence.size_in_bits() < values.len() * 24);
+
+        let extremes = EliasFano::from_sorted(&[1, u64::MAX - 1, u64::MAX]).unwrap();
+        assert_eq!(vec![1, u64::MAX - 1, u64::MAX], extremes.iter().collect::<Vec<_>>());
+        assert_eq!(Some((1, u64::MAX - 1)), extremes.next_geq(2));
+    }
+
+    #[test]
+    fn test_unsorted_and_empty() {
+        assert_eq!(None, EliasFano::from_sorted(&[5, 4]));
+
+        let empty = EliasFano::from_sorted(&[]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(None, empty.get(0));
+        assert_eq!(None, empty.next_geq(0));
+        assert_eq!(0, empty.iter().count());
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!("[1, 2, 9]", format!("{:?}", EliasFano::from_sorted(&[1, 2, 9]).unwrap()));
+    }
+
+}
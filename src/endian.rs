@@ -0,0 +1,282 @@
+//! Viewing the bits of an integer as they are laid out on the wire
+//!
+//! `has_x_bit` numbers the bits of an integer's value, so bit 0 is always the least signifigant
+//! bit no matter how the integer is stored. Views made with `wire` and `wire_mut` number bits by
+//! their place in the integer's bytes instead, in big-endian or little-endian byte order. Bit X of
+//! a view is bit `X % 8` of byte `X / 8`, where byte 0 is the first byte on the wire.
+//!
+//! Bits inside each byte are numbered from the least signifigant bit, like register bits. Views
+//! order in units of one byte, so `ordered::<Msb0>()` on a view numbers them from the most
+//! signifigant bit of each byte instead.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use {BitInformation, BitIteration, BitManipulation, BitOrdering, BitWidth};
+
+/// The order an integer's bytes are laid out in
+pub trait ByteOrder {
+
+    /// Translates byte X on the wire to the matching byte of the value, counting from the least
+    /// signifigant byte
+    fn value_byte(byte: usize, bytes: usize) -> usize;
+
+}
+
+/// The most signifigant byte comes first, as in network byte order
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigEndian;
+
+/// The least signifigant byte comes first
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LittleEndian;
+
+/// The byte order of the target platform
+#[cfg(target_endian = "big")]
+pub type NativeEndian = BigEndian;
+
+/// The byte order of the target platform
+#[cfg(target_endian = "little")]
+pub type NativeEndian = LittleEndian;
+
+impl ByteOrder for BigEndian {
+    fn value_byte(byte: usize, bytes: usize) -> usize {
+        bytes - 1 - byte
+    }
+}
+
+impl ByteOrder for LittleEndian {
+    fn value_byte(byte: usize, _bytes: usize) -> usize {
+        byte
+    }
+}
+
+/// Translates wire bit X to the matching value bit of a type with some number of bytes
+fn value_position<E: ByteOrder>(position: usize, bytes: usize) -> usize {
+    E::value_byte(position / 8, bytes) * 8 + position % 8
+}
+
+/// Access to an integer's bits by where they sit in its bytes
+pub trait WireBitInformation: BitWidth + Sized {
+
+    /// Checks to see if bit X of byte Y on the wire is set, in byte order E
+    fn has_wire_bit<E: ByteOrder>(&self, byte: usize, position: usize) -> bool {
+        position < 8 && self.wire::<E>().has_x_bit(byte * 8 + position)
+    }
+
+    /// Gets a view of this integer's bits as they are laid out in byte order E
+    fn wire<E: ByteOrder>(&self) -> WireBits<'_, E, Self> {
+        WireBits { value: self, order: PhantomData }
+    }
+
+    /// Gets a mutable view of this integer's bits as they are laid out in byte order E
+    fn wire_mut<E: ByteOrder>(&mut self) -> WireBitsMut<'_, E, Self> where Self: BitManipulation {
+        WireBitsMut { value: self, order: PhantomData }
+    }
+
+}
+
+/// A view of an integer's bits as they are laid out in byte order E
+pub struct WireBits<'a, E, T: 'a> {
+    value: &'a T,
+    order: PhantomData<E>,
+}
+
+impl<'a, E, T: 'a> Clone for WireBits<'a, E, T> {
+    fn clone(&self) -> WireBits<'a, E, T> {
+        *self
+    }
+}
+
+impl<'a, E, T: 'a> Copy for WireBits<'a, E, T> {}
+
+impl<'a, E: ByteOrder, T: BitWidth> BitInformation for WireBits<'a, E, T> {
+
+    /// Gets the number of bits in the viewed integer
+    fn number_of_bits(&self) -> usize {
+        T::BITS
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.is_bit_in_bounds(position) && self.value.has_x_bit(value_position::<E>(position, T::BITS / 8))
+    }
+
+}
+
+impl<'a, E: ByteOrder, T: BitWidth> BitIteration for WireBits<'a, E, T> {}
+
+impl<'a, E: ByteOrder, T: BitWidth> BitOrdering for WireBits<'a, E, T> {
+
+    /// Gets the number of bits in one byte, so orders number the bits of each byte in turn
+    fn ordering_unit(&self) -> usize {
+        8
+    }
+
+}
+
+impl<'a, E: ByteOrder, T: BitWidth> fmt::Debug for WireBits<'a, E, T> {
+
+    /// Formats every byte in wire order, most signifigant bit first, separated by spaces
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in 0..T::BITS / 8 {
+            if byte > 0 {
+                f.write_str(" ")?;
+            }
+            for bit in (0..8).rev() {
+                f.write_str(if self.has_x_bit(byte * 8 + bit) { "1" } else { "0" })?;
+            }
+        }
+        Ok(())
+    }
+
+}
+
+/// A mutable view of an integer's bits as they are laid out in byte order E
+pub struct WireBitsMut<'a, E, T: 'a> {
+    value: &'a mut T,
+    order: PhantomData<E>,
+}
+
+impl<'a, E: ByteOrder, T: BitWidth> BitInformation for WireBitsMut<'a, E, T> {
+
+    /// Gets the number of bits in the viewed integer
+    fn number_of_bits(&self) -> usize {
+        T::BITS
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.is_bit_in_bounds(position) && self.value.has_x_bit(value_position::<E>(position, T::BITS / 8))
+    }
+
+}
+
+impl<'a, E: ByteOrder, T: BitWidth + BitManipulation> BitManipulation for WireBitsMut<'a, E, T> {
+
+    fn set_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.value.set_x_bit(value_position::<E>(position, T::BITS / 8));
+        }
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.value.clear_x_bit(value_position::<E>(position, T::BITS / 8));
+        }
+    }
+
+}
+
+impl<'a, E: ByteOrder, T: BitWidth> BitIteration for WireBitsMut<'a, E, T> {}
+
+impl<'a, E: ByteOrder, T: BitWidth> BitOrdering for WireBitsMut<'a, E, T> {
+
+    /// Gets the number of bits in one byte, so orders number the bits of each byte in turn
+    fn ordering_unit(&self) -> usize {
+        8
+    }
+
+}
+
+macro_rules! impl_wire_bit_information {
+    ($($t:ty),*) => {
+        $(
+            impl WireBitInformation for $t {}
+        )*
+    }
+}
+
+impl_wire_bit_information!(u16, i16, u32, i32, u64, i64, u128, i128);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+    use Msb0;
+
+    #[test]
+    fn test_value_byte() {
+        assert_eq!(3, BigEndian::value_byte(0, 4));
+        assert_eq!(0, BigEndian::value_byte(3, 4));
+        assert_eq!(0, LittleEndian::value_byte(0, 4));
+        assert_eq!(3, LittleEndian::value_byte(3, 4));
+    }
+
+    #[test]
+    fn test_wire_bits_u16() {
+        let num = 0x0102u16;
+
+        assert!(num.wire::<BigEndian>().has_x_bit(0));
+        assert!(num.wire::<BigEndian>().has_x_bit(9));
+        assert!(num.wire::<LittleEndian>().has_x_bit(1));
+        assert!(num.wire::<LittleEndian>().has_x_bit(8));
+        assert!(!num.wire::<BigEndian>().has_x_bit(16));
+        assert_eq!(vec![0, 9], num.wire::<BigEndian>().ones().collect::<Vec<_>>());
+        assert_eq!(vec![1, 8], num.wire::<LittleEndian>().ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_has_wire_bit_u32() {
+        let num = 0x12345678u32;
+
+        for (byte, &expected) in num.to_be_bytes().iter().enumerate() {
+            for position in 0..8 {
+                assert_eq!(expected.has_x_bit(position), num.has_wire_bit::<BigEndian>(byte, position));
+            }
+        }
+        for (byte, &expected) in num.to_le_bytes().iter().enumerate() {
+            for position in 0..8 {
+                assert_eq!(expected.has_x_bit(position), num.has_wire_bit::<LittleEndian>(byte, position));
+            }
+        }
+        assert!(!num.has_wire_bit::<BigEndian>(0, 8));
+        assert!(!num.has_wire_bit::<BigEndian>(4, 0));
+    }
+
+    #[test]
+    fn test_wire_bits_u64_u128() {
+        let num = 0x8000000000000001u64;
+
+        assert_eq!(vec![7, 56], num.wire::<BigEndian>().ones().collect::<Vec<_>>());
+        assert!((1u128 << 127).has_wire_bit::<BigEndian>(0, 7));
+        assert!((1u128 << 127).has_wire_bit::<LittleEndian>(15, 7));
+        assert!(i64::MIN.has_wire_bit::<BigEndian>(0, 7));
+    }
+
+    #[test]
+    fn test_wire_bits_native() {
+        let num = 0xCAFEu16;
+        let bytes = num.to_ne_bytes();
+
+        assert_eq!(bytes[..].bits().collect::<Vec<_>>(), num.wire::<NativeEndian>().bits().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_wire_bits_msb0() {
+        let num = 0x4001u16;
+
+        assert_eq!(vec![1, 15], num.wire::<BigEndian>().ordered::<Msb0>().ones().collect::<Vec<_>>());
+        assert_eq!(vec![7, 9], num.wire::<LittleEndian>().ordered::<Msb0>().ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_wire_bits_mut() {
+        let mut num = 0u32;
+
+        num.wire_mut::<BigEndian>().set_x_bit(0);
+        assert_eq!(0x01000000, num);
+        num.wire_mut::<LittleEndian>().set_x_bit(15);
+        assert_eq!(0x01008000, num);
+        num.wire_mut::<BigEndian>().toggle_x_bit(0);
+        num.wire_mut::<BigEndian>().set_x_bit(32);
+        assert_eq!(0x00008000, num);
+        assert!(num.wire_mut::<BigEndian>().try_clear_x_bit(32).is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!("00010010 00110100", format!("{:?}", 0x1234u16.wire::<BigEndian>()));
+        assert_eq!("00110100 00010010", format!("{:?}", 0x1234u16.wire::<LittleEndian>()));
+    }
+
+}
@@ -0,0 +1,64 @@
+//! Errors reported by bit_utils
+
+use core::error::Error;
+use core::fmt;
+
+/// An error from a checked bit operation
+///
+/// More kinds of error may be added later, so matches on it need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BitError {
+
+    /// The bit position is not within the type
+    PositionOutOfBounds {
+        /// The requested bit position
+        position: usize,
+        /// The number of bits in the type
+        number_of_bits: usize,
+    },
+
+    /// The range of bits is backwards or does not fit within the type
+    InvalidRange {
+        /// The first bit of the range
+        start: usize,
+        /// One past the last bit of the range
+        end: usize,
+        /// The number of bits in the type
+        number_of_bits: usize,
+    },
+
+    /// The value has bits set outside of the field it is being stored in
+    FieldOverflow {
+        /// The number of bits in the field
+        width: usize,
+    },
+
+    /// Serialized data is cut short or does not follow its format
+    MalformedData {
+        /// The byte offset the problem was found at
+        offset: usize,
+    },
+
+    /// The filters have different sizes or numbers of hash functions
+    IncompatibleFilters,
+
+}
+
+impl fmt::Display for BitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BitError::PositionOutOfBounds { position, number_of_bits } => {
+                write!(f, "bit {} is out of bounds for a type with {} bits", position, number_of_bits)
+            }
+            BitError::InvalidRange { start, end, number_of_bits } => {
+                write!(f, "bit range {}..{} is invalid for a type with {} bits", start, end, number_of_bits)
+            }
+            BitError::FieldOverflow { width } => write!(f, "value does not fit in a {} bit field", width),
+            BitError::MalformedData { offset } => write!(f, "serialized data is malformed at byte {}", offset),
+            BitError::IncompatibleFilters => write!(f, "filters have different sizes or numbers of hash functions"),
+        }
+    }
+}
+
+impl Error for BitError {}
@@ -0,0 +1,600 @@
+//! Bitmaps compressed with the Enhanced Word-Aligned Hybrid (EWAH) scheme
+//!
+//! An EWAH bitmap is a sequence of 64-bit marker words, each followed by some literal words. A
+//! marker says how many words of all clear or all set bits come next, and then how many literal
+//! words follow it, copied as they are. Bit 0 of a marker is the bit the run is filled with, bits 1
+//! to 32 are the length of the run in words, and bits 33 to 63 are the number of literal words.
+//! This is the same layout JavaEWAH uses.
+//!
+//! Long runs of clear bits take up almost no room, and AND, OR, XOR and NOT work on whole runs at a
+//! time without decompressing them.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{BitAnd, BitOr, BitXor, Not, Range};
+
+use words::{words_for_bits, WORD_BITS};
+use {BitInformation, BitVec};
+
+/// The longest run a marker word can hold, in words
+const MAX_RUN: u64 = (1 << 32) - 1;
+
+/// The most literal words that can follow a marker word
+const MAX_LITERALS: u64 = (1 << 31) - 1;
+
+/// Gets the bit a marker word's run is filled with
+fn running_bit(marker: u64) -> bool {
+    marker & 1 == 1
+}
+
+/// Gets the length in words of a marker word's run
+fn running_length(marker: u64) -> u64 {
+    (marker >> 1) & MAX_RUN
+}
+
+/// Gets the number of literal words that follow a marker word
+fn literal_count(marker: u64) -> u64 {
+    marker >> 33
+}
+
+/// Gets a word with every bit set to `bit`
+fn fill(bit: bool) -> u64 {
+    if bit { u64::MAX } else { 0 }
+}
+
+/// A stretch of uncompressed words
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Chunk {
+
+    /// Some number of words with every bit set to the same value
+    Run(bool, usize),
+
+    /// A single word copied as it is
+    Literal(u64),
+
+}
+
+/// Reads the chunks out of compressed words
+#[derive(Clone, Debug)]
+struct Cursor<'a> {
+    words: &'a [u64],
+    position: usize,
+    running_bit: bool,
+    run_left: usize,
+    literals_left: usize,
+}
+
+impl<'a> Cursor<'a> {
+
+    fn new(words: &'a [u64]) -> Cursor<'a> {
+        Cursor { words, position: 0, running_bit: false, run_left: 0, literals_left: 0 }
+    }
+
+    /// Gets the next chunk, or None if every word has been read
+    fn next_chunk(&mut self) -> Option<Chunk> {
+        loop {
+            if self.run_left > 0 {
+                let length = self.run_left;
+                self.run_left = 0;
+                return Some(Chunk::Run(self.running_bit, length));
+            }
+
+            if self.literals_left > 0 {
+                self.literals_left -= 1;
+                self.position += 1;
+                return Some(Chunk::Literal(self.words[self.position - 1]));
+            }
+
+            let marker = *self.words.get(self.position)?;
+            self.position += 1;
+            self.running_bit = running_bit(marker);
+            self.run_left = running_length(marker) as usize;
+            self.literals_left = literal_count(marker) as usize;
+        }
+    }
+
+    /// Moves past X words of a chunk, reading the next chunk once it is used up
+    fn consume(&mut self, chunk: Option<Chunk>, count: usize) -> Option<Chunk> {
+        match chunk {
+            Some(Chunk::Run(bit, length)) if length > count => Some(Chunk::Run(bit, length - count)),
+            None => None,
+            _ => self.next_chunk(),
+        }
+    }
+
+}
+
+/// Appends uncompressed words, compressing them as it goes
+#[derive(Clone, Debug)]
+struct Builder {
+    words: Vec<u64>,
+    marker: usize,
+}
+
+impl Builder {
+
+    fn new() -> Builder {
+        Builder { words: vec![0], marker: 0 }
+    }
+
+    /// Appends X words with every bit set to `bit`
+    fn push_run(&mut self, bit: bool, mut count: usize) {
+        while count > 0 {
+            let marker = self.words[self.marker];
+            let length = running_length(marker);
+
+            if literal_count(marker) == 0 && (length == 0 || running_bit(marker) == bit) && length < MAX_RUN {
+                let added = (count as u64).min(MAX_RUN - length);
+                self.words[self.marker] = ((length + added) << 1) | bit as u64;
+                count -= added as usize;
+            } else {
+                self.start_marker();
+            }
+        }
+    }
+
+    /// Appends a single word
+    fn push_word(&mut self, word: u64) {
+        match word {
+            0 => self.push_run(false, 1),
+            u64::MAX => self.push_run(true, 1),
+            _ => {
+                if literal_count(self.words[self.marker]) == MAX_LITERALS {
+                    self.start_marker();
+                }
+
+                self.words.push(word);
+                self.words[self.marker] += 1 << 33;
+            },
+        }
+    }
+
+    /// Starts a new marker word with no run and no literals
+    fn start_marker(&mut self) {
+        self.words.push(0);
+        self.marker = self.words.len() - 1;
+    }
+
+    fn finish(self, len: usize) -> EwahBitmap {
+        EwahBitmap { words: self.words, len }
+    }
+
+}
+
+/// A bitmap compressed with EWAH
+#[derive(Clone)]
+pub struct EwahBitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl EwahBitmap {
+
+    /// Creates an empty EwahBitmap
+    pub fn new() -> EwahBitmap {
+        Builder::new().finish(0)
+    }
+
+    /// Compresses the bits of a BitVec
+    pub fn from_bit_vec(bits: &BitVec) -> EwahBitmap {
+        let mut builder = Builder::new();
+        for &word in bits.as_words() {
+            builder.push_word(word);
+        }
+        builder.finish(bits.len())
+    }
+
+    /// Decompresses this bitmap into a BitVec
+    pub fn to_bit_vec(&self) -> BitVec {
+        let mut words = Vec::with_capacity(words_for_bits(self.len));
+        let mut cursor = Cursor::new(&self.words);
+
+        while let Some(chunk) = cursor.next_chunk() {
+            match chunk {
+                Chunk::Run(bit, length) => words.resize(words.len() + length, fill(bit)),
+                Chunk::Literal(word) => words.push(word),
+            }
+        }
+
+        words.resize(words_for_bits(self.len), 0);
+        let mut bits = BitVec::from_words(&words);
+        bits.truncate(self.len);
+        bits
+    }
+
+    /// Gets the compressed words, in the JavaEWAH layout
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Gets the number of bits in this bitmap
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if this bitmap has no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets bit X, or None if it is out of bounds
+    ///
+    /// This walks the compressed words from the start, so iterating is much faster than calling it
+    /// for every bit.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+
+        let target = index / WORD_BITS;
+        let mut word = 0;
+        let mut cursor = Cursor::new(&self.words);
+
+        while let Some(chunk) = cursor.next_chunk() {
+            match chunk {
+                Chunk::Run(bit, length) if target < word + length => return Some(bit),
+                Chunk::Run(_, length) => word += length,
+                Chunk::Literal(literal) if target == word => return Some(literal.has_x_bit(index % WORD_BITS)),
+                Chunk::Literal(_) => word += 1,
+            }
+        }
+
+        Some(false)
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        let mut ones = 0;
+        let mut cursor = Cursor::new(&self.words);
+
+        while let Some(chunk) = cursor.next_chunk() {
+            ones += match chunk {
+                Chunk::Run(true, length) => length * WORD_BITS,
+                Chunk::Run(false, _) => 0,
+                Chunk::Literal(word) => word.count_ones() as usize,
+            };
+        }
+
+        ones
+    }
+
+    /// Iterates over the positions of every set bit in ascending order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { cursor: Cursor::new(&self.words), word: 0, literal: 0, base: 0, run: 0..0 }
+    }
+
+    /// Gets a new bitmap with the bits set in both bitmaps
+    pub fn and(&self, other: &EwahBitmap) -> EwahBitmap {
+        self.combine(other, |ours, theirs| ours & theirs)
+    }
+
+    /// Gets a new bitmap with the bits set in either bitmap
+    pub fn or(&self, other: &EwahBitmap) -> EwahBitmap {
+        self.combine(other, |ours, theirs| ours | theirs)
+    }
+
+    /// Gets a new bitmap with the bits set in exactly one of the bitmaps
+    pub fn xor(&self, other: &EwahBitmap) -> EwahBitmap {
+        self.combine(other, |ours, theirs| ours ^ theirs)
+    }
+
+    /// Gets a new bitmap with every bit flipped
+    pub fn not(&self) -> EwahBitmap {
+        let words = words_for_bits(self.len);
+        let used = self.len % WORD_BITS;
+        let last_mask = if used == 0 { u64::MAX } else { (1 << used) - 1 };
+
+        let mut builder = Builder::new();
+        let mut cursor = Cursor::new(&self.words);
+        let mut word = 0;
+
+        while let Some(chunk) = cursor.next_chunk() {
+            match chunk {
+                Chunk::Run(bit, length) if word + length == words && used != 0 => {
+                    builder.push_run(!bit, length - 1);
+                    builder.push_word(fill(!bit) & last_mask);
+                    word += length;
+                },
+                Chunk::Run(bit, length) => {
+                    builder.push_run(!bit, length);
+                    word += length;
+                },
+                Chunk::Literal(literal) => {
+                    word += 1;
+                    builder.push_word(if word == words { !literal & last_mask } else { !literal });
+                },
+            }
+        }
+
+        builder.finish(self.len)
+    }
+
+    /// Combines two bitmaps a chunk at a time, treating the shorter one as padded with clear bits
+    fn combine<F: Fn(u64, u64) -> u64>(&self, other: &EwahBitmap, operation: F) -> EwahBitmap {
+        let mut builder = Builder::new();
+        let (mut ours, mut theirs) = (Cursor::new(&self.words), Cursor::new(&other.words));
+        let (mut our_chunk, mut their_chunk) = (ours.next_chunk(), theirs.next_chunk());
+
+        while our_chunk.is_some() || their_chunk.is_some() {
+            let padding = Chunk::Run(false, usize::MAX);
+
+            let consumed = match (our_chunk.unwrap_or(padding), their_chunk.unwrap_or(padding)) {
+                (Chunk::Run(our_bit, our_length), Chunk::Run(their_bit, their_length)) => {
+                    let length = our_length.min(their_length);
+                    builder.push_run(operation(fill(our_bit), fill(their_bit)) == u64::MAX, length);
+                    length
+                },
+                (Chunk::Run(bit, _), Chunk::Literal(word)) => {
+                    builder.push_word(operation(fill(bit), word));
+                    1
+                },
+                (Chunk::Literal(word), Chunk::Run(bit, _)) => {
+                    builder.push_word(operation(word, fill(bit)));
+                    1
+                },
+                (Chunk::Literal(our_word), Chunk::Literal(their_word)) => {
+                    builder.push_word(operation(our_word, their_word));
+                    1
+                },
+            };
+
+            our_chunk = ours.consume(our_chunk, consumed);
+            their_chunk = theirs.consume(their_chunk, consumed);
+        }
+
+        builder.finish(self.len.max(other.len))
+    }
+
+}
+
+impl Default for EwahBitmap {
+    fn default() -> EwahBitmap {
+        EwahBitmap::new()
+    }
+}
+
+impl PartialEq for EwahBitmap {
+
+    /// Compares the bits, however they happen to be compressed
+    fn eq(&self, other: &EwahBitmap) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+
+}
+
+impl Eq for EwahBitmap {}
+
+impl BitInformation for EwahBitmap {
+
+    /// Gets the number of bits contained in this EwahBitmap
+    fn number_of_bits(&self) -> usize {
+        self.len
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.get(position).unwrap_or(false)
+    }
+
+}
+
+impl<'a> From<&'a BitVec> for EwahBitmap {
+    fn from(bits: &'a BitVec) -> EwahBitmap {
+        EwahBitmap::from_bit_vec(bits)
+    }
+}
+
+impl<'a> From<&'a EwahBitmap> for BitVec {
+    fn from(bitmap: &'a EwahBitmap) -> BitVec {
+        bitmap.to_bit_vec()
+    }
+}
+
+impl<'b> BitAnd<&'b EwahBitmap> for &EwahBitmap {
+    type Output = EwahBitmap;
+
+    fn bitand(self, other: &'b EwahBitmap) -> EwahBitmap {
+        self.and(other)
+    }
+}
+
+impl<'b> BitOr<&'b EwahBitmap> for &EwahBitmap {
+    type Output = EwahBitmap;
+
+    fn bitor(self, other: &'b EwahBitmap) -> EwahBitmap {
+        self.or(other)
+    }
+}
+
+impl<'b> BitXor<&'b EwahBitmap> for &EwahBitmap {
+    type Output = EwahBitmap;
+
+    fn bitxor(self, other: &'b EwahBitmap) -> EwahBitmap {
+        self.xor(other)
+    }
+}
+
+impl Not for &EwahBitmap {
+    type Output = EwahBitmap;
+
+    fn not(self) -> EwahBitmap {
+        EwahBitmap::not(self)
+    }
+}
+
+impl fmt::Debug for EwahBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a EwahBitmap {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the positions of set bits in an EwahBitmap
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    cursor: Cursor<'a>,
+    word: usize,
+    literal: u64,
+    base: usize,
+    run: Range<usize>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(position) = self.run.next() {
+                return Some(position);
+            }
+
+            if self.literal != 0 {
+                let offset = self.literal.trailing_zeros() as usize;
+                self.literal &= self.literal - 1;
+                return Some(self.base + offset);
+            }
+
+            match self.cursor.next_chunk()? {
+                Chunk::Run(bit, length) => {
+                    if bit {
+                        self.run = self.word * WORD_BITS..(self.word + length) * WORD_BITS;
+                    }
+                    self.word += length;
+                },
+                Chunk::Literal(word) => {
+                    self.literal = word;
+                    self.base = self.word * WORD_BITS;
+                    self.word += 1;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+
+    /// Makes a BitVec of `len` bits with the given positions set
+    fn bit_vec(len: usize, positions: &[usize]) -> BitVec {
+        let mut bits = BitVec::from_elem(len, false);
+        for &position in positions {
+            bits.set(position, true);
+        }
+        bits
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bits = bit_vec(10_000, &[0, 63, 64, 5_000, 9_999]);
+        let bitmap = EwahBitmap::from_bit_vec(&bits);
+
+        assert_eq!(10_000, bitmap.len());
+        assert_eq!(bits, bitmap.to_bit_vec());
+        assert_eq!(bits, BitVec::from(&bitmap));
+        assert_eq!(vec![0, 63, 64, 5_000, 9_999], bitmap.iter().collect::<Vec<_>>());
+        assert_eq!(5, bitmap.count_ones());
+        assert!(bitmap.as_words().len() < 10);
+    }
+
+    #[test]
+    fn test_marker_layout() {
+        let mut bits = BitVec::from_elem(64 * 5, false);
+        bits.fill(64 * 2, 64 * 4, true);
+        bits.set(64 * 4 + 1, true);
+        let bitmap = EwahBitmap::from(&bits);
+
+        assert_eq!(&[2 << 1, (2 << 1) | 1 | (1 << 33), 2], bitmap.as_words());
+    }
+
+    #[test]
+    fn test_runs_of_ones() {
+        let bits = BitVec::from_elem(200, true);
+        let bitmap = EwahBitmap::from_bit_vec(&bits);
+
+        assert_eq!(200, bitmap.count_ones());
+        assert_eq!((0..200).collect::<Vec<_>>(), bitmap.iter().collect::<Vec<_>>());
+        assert_eq!(bits, bitmap.to_bit_vec());
+    }
+
+    #[test]
+    fn test_get() {
+        let bitmap = EwahBitmap::from_bit_vec(&bit_vec(1_000, &[3, 700]));
+
+        assert_eq!(Some(true), bitmap.get(3));
+        assert_eq!(Some(true), bitmap.get(700));
+        assert_eq!(Some(false), bitmap.get(500));
+        assert_eq!(None, bitmap.get(1_000));
+        assert!(bitmap.has_x_bit(700));
+        assert!(!bitmap.has_x_bit(1_000));
+        assert_eq!(1_000, bitmap.number_of_bits());
+    }
+
+    #[test]
+    fn test_logical_operations() {
+        let a = bit_vec(5_000, &[1, 100, 2_000, 4_999]);
+        let mut b = bit_vec(5_000, &[100, 2_001]);
+        b.fill(3_000, 4_500, true);
+        let (x, y) = (EwahBitmap::from(&a), EwahBitmap::from(&b));
+
+        assert_eq!(&a & &b, x.and(&y).to_bit_vec());
+        assert_eq!(&a | &b, x.or(&y).to_bit_vec());
+        assert_eq!(&a ^ &b, x.xor(&y).to_bit_vec());
+        assert_eq!(!&a, x.not().to_bit_vec());
+        assert_eq!(!&b, (!&y).to_bit_vec());
+        assert_eq!(x.and(&y), &x & &y);
+        assert_eq!(x.or(&y), &x | &y);
+        assert_eq!(x.xor(&y), &x ^ &y);
+    }
+
+    #[test]
+    fn test_logical_operations_different_lengths() {
+        let short = EwahBitmap::from_bit_vec(&bit_vec(100, &[5, 99]));
+        let long = EwahBitmap::from_bit_vec(&bit_vec(1_000, &[5, 500]));
+
+        assert_eq!(1_000, short.or(&long).len());
+        assert_eq!(vec![5, 99, 500], short.or(&long).iter().collect::<Vec<_>>());
+        assert_eq!(vec![5], short.and(&long).iter().collect::<Vec<_>>());
+        assert_eq!(vec![99, 500], long.xor(&short).iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_not_masks_unused_bits() {
+        let bitmap = EwahBitmap::from_bit_vec(&bit_vec(70, &[]));
+        let flipped = bitmap.not();
+
+        assert_eq!(70, flipped.count_ones());
+        assert_eq!(bitmap, flipped.not());
+
+        let ones = EwahBitmap::from_bit_vec(&BitVec::from_elem(130, true));
+        assert_eq!(0, ones.not().count_ones());
+        assert_eq!(130, ones.not().not().count_ones());
+    }
+
+    #[test]
+    fn test_empty() {
+        let empty = EwahBitmap::new();
+
+        assert!(empty.is_empty());
+        assert_eq!(0, empty.iter().count());
+        assert_eq!(BitVec::new(), empty.to_bit_vec());
+        assert_eq!(empty, empty.not());
+        assert_eq!(EwahBitmap::default(), empty.or(&empty));
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!("{2, 65}", format!("{:?}", EwahBitmap::from_bit_vec(&bit_vec(100, &[2, 65]))));
+    }
+
+}
@@ -0,0 +1,221 @@
+//! Extracting and inserting fields made of several bits
+//!
+//! Fields are always treated as raw bits, so a field read from a signed integer is not sign
+//! extended and a negative value only fits in a field as wide as the whole integer.
+
+use core::ops::Range;
+
+use {BitError, BitInformation};
+
+/// Reads and writes ranges of bits as a single value
+pub trait BitFields: BitInformation + Sized {
+
+    /// Gets the bits in `range`, shifted down so that `range.start` becomes bit 0
+    fn get_bits(&self, range: Range<usize>) -> Result<Self, BitError>;
+
+    /// Replaces the bits in `range` with `value`, failing if `value` is wider than the range
+    fn set_bits(&mut self, range: Range<usize>, value: Self) -> Result<(), BitError>;
+
+    /// Gets a copy of this value with the bits in `range` replaced by `value`
+    fn with_bits(mut self, range: Range<usize>, value: Self) -> Result<Self, BitError> {
+        self.set_bits(range, value)?;
+        Ok(self)
+    }
+
+}
+
+/// Checks that a range of bits fits within a type
+fn check_range<T: BitInformation + ?Sized>(value: &T, range: &Range<usize>) -> Result<(), BitError> {
+    if range.start <= range.end && range.end <= value.number_of_bits() {
+        Ok(())
+    } else {
+        Err(BitError::InvalidRange { start: range.start, end: range.end, number_of_bits: value.number_of_bits() })
+    }
+}
+
+macro_rules! impl_bit_fields {
+    ($($t:ty => $unsigned:ty),*) => {
+        $(
+            impl BitFields for $t {
+
+                fn get_bits(&self, range: Range<usize>) -> Result<$t, BitError> {
+                    check_range(self, &range)?;
+
+                    let width = range.end - range.start;
+                    if width == 0 {
+                        return Ok(0);
+                    }
+
+                    let mask = <$unsigned>::MAX >> (self.number_of_bits() - width);
+                    Ok(((*self as $unsigned >> range.start) & mask) as $t)
+                }
+
+                fn set_bits(&mut self, range: Range<usize>, value: $t) -> Result<(), BitError> {
+                    check_range(self, &range)?;
+
+                    let width = range.end - range.start;
+                    if width == 0 {
+                        return if value == 0 { Ok(()) } else { Err(BitError::FieldOverflow { width }) };
+                    }
+
+                    let mask = <$unsigned>::MAX >> (self.number_of_bits() - width);
+                    if value as $unsigned & !mask != 0 {
+                        return Err(BitError::FieldOverflow { width });
+                    }
+
+                    let cleared = *self as $unsigned & !(mask << range.start);
+                    *self = (cleared | ((value as $unsigned) << range.start)) as $t;
+                    Ok(())
+                }
+
+            }
+        )*
+    }
+}
+
+impl_bit_fields!(u8 => u8, i8 => u8, u16 => u16, i16 => u16, u32 => u32, i32 => u32,
+                 u64 => u64, i64 => u64, u128 => u128, i128 => u128, usize => usize, isize => usize);
+
+#[allow(overflowing_literals)] //Just for tests, don't do this in real life, kids!
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_get_bits_u8() {
+        let num = 0b10110100u8;
+
+        assert_eq!(Ok(0b0100u8), num.get_bits(0..4));
+        assert_eq!(Ok(0b1011u8), num.get_bits(4..8));
+        assert_eq!(Ok(0b101u8), num.get_bits(2..5));
+        assert_eq!(Ok(num), num.get_bits(0..8));
+        assert_eq!(Ok(0u8), num.get_bits(8..8));
+    }
+
+    #[test]
+    fn test_set_bits_u8() {
+        let mut num = 0b10110100u8;
+
+        assert_eq!(Ok(()), num.set_bits(0..4, 0b1111));
+        assert_eq!(0b10111111u8, num);
+        assert_eq!(Ok(()), num.set_bits(6..8, 0b01));
+        assert_eq!(0b01111111u8, num);
+        assert_eq!(Ok(()), num.set_bits(0..8, 0xA5));
+        assert_eq!(0xA5u8, num);
+        assert_eq!(Ok(0xF5u8), num.with_bits(4..8, 0xF));
+    }
+
+    #[test]
+    fn test_get_set_bits_i8() {
+        let mut num = 0b10110100i8;
+
+        assert_eq!(Ok(0b1011i8), num.get_bits(4..8));
+        assert_eq!(Ok(()), num.set_bits(4..8, 0b0111));
+        assert_eq!(0b01110100i8, num);
+        assert_eq!(Ok(()), num.set_bits(0..8, -1));
+        assert_eq!(-1i8, num);
+        assert_eq!(Err(BitError::FieldOverflow { width: 4 }), num.set_bits(0..4, -1));
+    }
+
+    #[test]
+    fn test_get_set_bits_u16() {
+        let mut num = 0xBEEFu16;
+
+        assert_eq!(Ok(0xEEu16), num.get_bits(4..12));
+        assert_eq!(Ok(()), num.set_bits(4..12, 0x12));
+        assert_eq!(0xB12Fu16, num);
+    }
+
+    #[test]
+    fn test_get_set_bits_i16() {
+        let mut num = -1i16;
+
+        assert_eq!(Ok(0x7Fi16), num.get_bits(9..16));
+        assert_eq!(Ok(()), num.set_bits(8..16, 0));
+        assert_eq!(0x00FFi16, num);
+    }
+
+    #[test]
+    fn test_get_set_bits_u32() {
+        let mut num = 0xDEADBEEFu32;
+
+        assert_eq!(Ok(0xDEADu32), num.get_bits(16..32));
+        assert_eq!(Ok(0xBEEFu32), num.get_bits(0..16));
+        assert_eq!(Ok(()), num.set_bits(8..24, 0xCAFE));
+        assert_eq!(0xDECAFEEFu32, num);
+    }
+
+    #[test]
+    fn test_get_set_bits_i32() {
+        let mut num = 0i32;
+
+        assert_eq!(Ok(()), num.set_bits(31..32, 1));
+        assert_eq!(i32::MIN, num);
+        assert_eq!(Ok(1i32), num.get_bits(31..32));
+    }
+
+    #[test]
+    fn test_get_set_bits_u64() {
+        let mut num = 0x0123456789ABCDEFu64;
+
+        assert_eq!(Ok(0x01234567u64), num.get_bits(32..64));
+        assert_eq!(Ok(()), num.set_bits(60..64, 0xF));
+        assert_eq!(0xF123456789ABCDEFu64, num);
+        assert_eq!(Ok(num), num.get_bits(0..64));
+    }
+
+    #[test]
+    fn test_get_set_bits_i64() {
+        let mut num = 0i64;
+
+        assert_eq!(Ok(()), num.set_bits(0..64, -2));
+        assert_eq!(-2i64, num);
+        assert_eq!(Ok(0x7FFFFFFFi64), num.get_bits(32..63));
+    }
+
+    #[test]
+    fn test_get_set_bits_u128() {
+        let mut num = 0x0123456789ABCDEF_FEDCBA9876543210u128;
+
+        assert_eq!(Ok(0x0123456789ABCDEFu128), num.get_bits(64..128));
+        assert_eq!(Ok(0x89ABCDEF_FEDCBA98u128), num.get_bits(32..96));
+        assert_eq!(Ok(()), num.set_bits(120..128, 0xFF));
+        assert_eq!(0xFF23456789ABCDEF_FEDCBA9876543210u128, num);
+        assert_eq!(Ok(num), num.get_bits(0..128));
+    }
+
+    #[test]
+    fn test_get_set_bits_i128() {
+        let mut num = 0i128;
+
+        assert_eq!(Ok(()), num.set_bits(0..128, -1));
+        assert_eq!(-1i128, num);
+        assert_eq!(Ok(()), num.set_bits(64..128, 0));
+        assert_eq!(Ok(0xFFFFi128), num.get_bits(48..80));
+        assert_eq!(Err(BitError::FieldOverflow { width: 64 }), num.set_bits(0..64, -1));
+    }
+
+    #[test]
+    fn test_get_set_bits_usize_isize() {
+        let mut unsigned = 0usize;
+        let mut signed = 0isize;
+
+        assert_eq!(Ok(()), unsigned.set_bits(3..7, 0b1001));
+        assert_eq!(0b1001000usize, unsigned);
+        assert_eq!(Ok(()), signed.set_bits(3..7, 0b1001));
+        assert_eq!(Ok(0b1001isize), signed.get_bits(3..7));
+    }
+
+    #[test]
+    fn test_bit_field_errors() {
+        let mut num = 0u8;
+
+        assert_eq!(Err(BitError::InvalidRange { start: 4, end: 9, number_of_bits: 8 }), num.get_bits(4..9));
+        assert_eq!(Err(BitError::InvalidRange { start: 5, end: 3, number_of_bits: 8 }), num.set_bits(Range { start: 5, end: 3 }, 0));
+        assert_eq!(Err(BitError::FieldOverflow { width: 3 }), num.set_bits(0..3, 0b1000));
+        assert_eq!(Err(BitError::FieldOverflow { width: 0 }), num.set_bits(2..2, 1));
+        assert_eq!(0u8, num);
+    }
+
+}
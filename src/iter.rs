@@ -0,0 +1,296 @@
+//! Iterating over bits, set and clear bit positions, and runs of equal bits
+
+use {BitInformation, BitWidth};
+
+/// Iterators over the bits of a type
+///
+/// Every iterator goes from the least signifigant bit upwards. `bits()` can be reversed to go
+/// from the most signifigant bit downwards instead.
+pub trait BitIteration: BitInformation {
+
+    /// Finds the first set bit at or after position X
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        (position..self.number_of_bits()).find(|&position| self.has_x_bit(position))
+    }
+
+    /// Finds the first clear bit at or after position X
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        (position..self.number_of_bits()).find(|&position| !self.has_x_bit(position))
+    }
+
+    /// Iterates over every bit as a bool
+    fn bits(&self) -> Bits<'_, Self> {
+        Bits { value: self, front: 0, back: self.number_of_bits() }
+    }
+
+    /// Iterates over the positions of every set bit
+    fn ones(&self) -> Ones<'_, Self> {
+        Ones { value: self, position: 0 }
+    }
+
+    /// Iterates over the positions of every clear bit
+    fn zeros(&self) -> Zeros<'_, Self> {
+        Zeros { value: self, position: 0 }
+    }
+
+    /// Iterates over runs of equal bits as (start, length, value)
+    fn runs(&self) -> Runs<'_, Self> {
+        Runs { value: self, position: 0 }
+    }
+
+}
+
+/// An iterator over every bit of a value
+#[derive(Debug)]
+pub struct Bits<'a, T: ?Sized + 'a> {
+    value: &'a T,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T: ?Sized + 'a> Clone for Bits<'a, T> {
+    fn clone(&self) -> Bits<'a, T> {
+        Bits { value: self.value, front: self.front, back: self.back }
+    }
+}
+
+impl<'a, T: BitInformation + ?Sized> Iterator for Bits<'a, T> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(self.value.has_x_bit(self.front - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T: BitInformation + ?Sized> DoubleEndedIterator for Bits<'a, T> {
+    fn next_back(&mut self) -> Option<bool> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(self.value.has_x_bit(self.back))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T: BitInformation + ?Sized> ExactSizeIterator for Bits<'a, T> {}
+
+/// An iterator over the positions of set bits
+#[derive(Debug)]
+pub struct Ones<'a, T: ?Sized + 'a> {
+    value: &'a T,
+    position: usize,
+}
+
+impl<'a, T: ?Sized + 'a> Clone for Ones<'a, T> {
+    fn clone(&self) -> Ones<'a, T> {
+        Ones { value: self.value, position: self.position }
+    }
+}
+
+impl<'a, T: BitIteration + ?Sized> Iterator for Ones<'a, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let found = self.value.next_set_bit(self.position)?;
+        self.position = found + 1;
+        Some(found)
+    }
+}
+
+/// An iterator over the positions of clear bits
+#[derive(Debug)]
+pub struct Zeros<'a, T: ?Sized + 'a> {
+    value: &'a T,
+    position: usize,
+}
+
+impl<'a, T: ?Sized + 'a> Clone for Zeros<'a, T> {
+    fn clone(&self) -> Zeros<'a, T> {
+        Zeros { value: self.value, position: self.position }
+    }
+}
+
+impl<'a, T: BitIteration + ?Sized> Iterator for Zeros<'a, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let found = self.value.next_clear_bit(self.position)?;
+        self.position = found + 1;
+        Some(found)
+    }
+}
+
+/// An iterator over runs of equal bits
+#[derive(Debug)]
+pub struct Runs<'a, T: ?Sized + 'a> {
+    value: &'a T,
+    position: usize,
+}
+
+impl<'a, T: ?Sized + 'a> Clone for Runs<'a, T> {
+    fn clone(&self) -> Runs<'a, T> {
+        Runs { value: self.value, position: self.position }
+    }
+}
+
+impl<'a, T: BitIteration + ?Sized> Iterator for Runs<'a, T> {
+    type Item = (usize, usize, bool);
+
+    fn next(&mut self) -> Option<(usize, usize, bool)> {
+        let start = self.position;
+        let value = self.value.try_has_x_bit(start).ok()?;
+        let end = if value {
+            self.value.next_clear_bit(start)
+        } else {
+            self.value.next_set_bit(start)
+        };
+
+        self.position = end.unwrap_or_else(|| self.value.number_of_bits());
+        Some((start, self.position - start, value))
+    }
+}
+
+macro_rules! impl_bit_iteration {
+    ($($t:ty => $unsigned:ty),*) => {
+        $(
+            impl BitIteration for $t {
+
+                fn next_set_bit(&self, position: usize) -> Option<usize> {
+                    if position >= <$t as BitWidth>::BITS {
+                        return None;
+                    }
+
+                    match (*self as $unsigned) >> position {
+                        0 => None,
+                        remaining => Some(position + remaining.trailing_zeros() as usize),
+                    }
+                }
+
+                fn next_clear_bit(&self, position: usize) -> Option<usize> {
+                    if position >= <$t as BitWidth>::BITS {
+                        return None;
+                    }
+
+                    match !(*self as $unsigned) >> position {
+                        0 => None,
+                        remaining => Some(position + remaining.trailing_zeros() as usize),
+                    }
+                }
+
+            }
+        )*
+    }
+}
+
+impl_bit_iteration!(u8 => u8, i8 => u8, u16 => u16, i16 => u16, u32 => u32, i32 => u32, u64 => u64,
+                    i64 => u64, u128 => u128, i128 => u128, usize => usize, isize => usize);
+
+#[allow(overflowing_literals)] //Just for tests, don't do this in real life, kids!
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_bits_u8() {
+        let num = 0b10110001u8;
+
+        assert_eq!(vec![true, false, false, false, true, true, false, true], num.bits().collect::<Vec<_>>());
+        assert_eq!(vec![true, false, true, true, false, false, false, true], num.bits().rev().collect::<Vec<_>>());
+        assert_eq!(8, num.bits().len());
+    }
+
+    #[test]
+    fn test_bits_i16() {
+        let num = -2i16;
+
+        assert_eq!(Some(false), num.bits().next());
+        assert_eq!(15, num.bits().filter(|&bit| bit).count());
+        assert_eq!(Some(true), num.bits().next_back());
+    }
+
+    #[test]
+    fn test_ones_u64() {
+        let num = (1u64 << 63) | (1u64 << 40) | 0b101;
+
+        assert_eq!(vec![0, 2, 40, 63], num.ones().collect::<Vec<_>>());
+        assert_eq!(0, 0u64.ones().count());
+        assert_eq!(64, (!0u64).ones().count());
+    }
+
+    #[test]
+    fn test_ones_i32() {
+        let num = i32::MIN | 0b1000;
+
+        assert_eq!(vec![3, 31], num.ones().collect::<Vec<_>>());
+        assert_eq!((0..32).collect::<Vec<_>>(), (-1i32).ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ones_u128() {
+        let num = (1u128 << 127) | (1u128 << 64) | 1;
+
+        assert_eq!(vec![0, 64, 127], num.ones().collect::<Vec<_>>());
+        assert_eq!(vec![0, 64, 127], ((1i128 << 127) | (1i128 << 64) | 1).ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_zeros_u16() {
+        let num = 0xFFF0u16;
+
+        assert_eq!(vec![0, 1, 2, 3], num.zeros().collect::<Vec<_>>());
+        assert_eq!(0, (!0u16).zeros().count());
+        assert_eq!(16, 0u16.zeros().count());
+    }
+
+    #[test]
+    fn test_zeros_usize_isize() {
+        let bits = 0usize.number_of_bits();
+
+        assert_eq!(bits - 1, (!0usize >> 1).zeros().next().unwrap());
+        assert_eq!(vec![0], (-2isize).zeros().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_runs_u8() {
+        let num = 0b11100110u8;
+
+        assert_eq!(vec![(0, 1, false), (1, 2, true), (3, 2, false), (5, 3, true)], num.runs().collect::<Vec<_>>());
+        assert_eq!(vec![(0, 8, false)], 0u8.runs().collect::<Vec<_>>());
+        assert_eq!(vec![(0, 8, true)], 0xFFu8.runs().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_runs_i64() {
+        let num = i64::MIN;
+
+        assert_eq!(vec![(0, 63, false), (63, 1, true)], num.runs().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_next_set_and_clear_bit() {
+        let num = 0b00101000u8;
+
+        assert_eq!(Some(3), num.next_set_bit(0));
+        assert_eq!(Some(5), num.next_set_bit(4));
+        assert_eq!(None, num.next_set_bit(6));
+        assert_eq!(None, num.next_set_bit(8));
+        assert_eq!(Some(4), num.next_clear_bit(3));
+        assert_eq!(Some(7), num.next_clear_bit(7));
+        assert_eq!(None, num.next_clear_bit(8));
+        assert_eq!(None, num.next_clear_bit(100));
+    }
+
+}
@@ -1,198 +1,398 @@
 //! Bit utilities for Rust
 //!
-//! This will eventually expand to include Nibbles and packing them into bytes and integers.
+//! Includes a 4-bit `Nibble` type, packing Nibbles into bytes and integers, and `NibbleVec` and
+//! `NibbleSlice` for sequences of Nibbles such as hex paths.
+//!
+//! This crate is `no_std`. Types that need a heap, such as `BitVec` and `NibbleVec`, are behind the
+//! `alloc` feature, which is enabled by default.
+
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(test)]
+#[macro_use]
+extern crate std;
+
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+mod atomic_bit_set;
+mod bit_array;
+#[cfg(feature = "alloc")]
+mod bit_matrix;
+#[cfg(feature = "alloc")]
+mod bit_set;
+#[cfg(feature = "alloc")]
+mod bit_vec;
+#[cfg(feature = "alloc")]
+mod bloom;
+#[cfg(feature = "alloc")]
+mod elias_fano;
+mod endian;
+mod error;
+#[cfg(feature = "alloc")]
+mod ewah;
+mod fields;
+mod iter;
+mod manipulation;
+mod nibble;
+#[cfg(feature = "alloc")]
+mod nibble_vec;
+mod order;
+mod packing;
+mod queries;
+#[cfg(feature = "alloc")]
+mod rank_select;
+#[cfg(feature = "alloc")]
+mod roaring;
+mod slice;
+#[cfg(feature = "alloc")]
+mod wavelet_matrix;
+mod words;
+
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+pub use atomic_bit_set::AtomicBitSet;
+pub use bit_array::BitArray;
+#[cfg(feature = "alloc")]
+pub use bit_matrix::{BitMatrix, Column, Row};
+#[cfg(feature = "alloc")]
+pub use bit_set::BitSet;
+#[cfg(feature = "alloc")]
+pub use bit_vec::BitVec;
+#[cfg(feature = "alloc")]
+pub use bloom::{BloomFilter, CountingBloomFilter};
+#[cfg(feature = "alloc")]
+pub use elias_fano::EliasFano;
+pub use endian::{BigEndian, ByteOrder, LittleEndian, NativeEndian, WireBitInformation, WireBits, WireBitsMut};
+pub use error::BitError;
+#[cfg(feature = "alloc")]
+pub use ewah::EwahBitmap;
+pub use fields::BitFields;
+pub use iter::{BitIteration, Bits, Ones, Runs, Zeros};
+pub use manipulation::BitManipulation;
+pub use nibble::Nibble;
+#[cfg(feature = "alloc")]
+pub use nibble_vec::{NibbleSlice, NibbleVec};
+pub use order::{BitOrder, BitOrdering, Lsb0, Msb0, Ordered, OrderedMut};
+pub use packing::{NibbleOrder, NibblePacking};
+pub use queries::BitQueries;
+#[cfg(feature = "alloc")]
+pub use rank_select::RankSelect;
+#[cfg(feature = "alloc")]
+pub use roaring::{RoaringBitmap, RoaringTreemap};
+#[cfg(feature = "alloc")]
+pub use wavelet_matrix::WaveletMatrix;
+pub use words::words_for_bits;
 
 pub trait BitInformation {
     
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize;
     
-    /// Checks to see if bit X is set
+    /// Checks to see if bit X is set, returning false if X is out of bounds
     fn has_x_bit(&self, position: usize) -> bool;
     
     /// Checks to see if the requested bit position is in bounds
     fn is_bit_in_bounds(&self, position: usize) -> bool {
-        position <= self.number_of_bits()
+        position < self.number_of_bits()
+    }
+    
+    /// Checks to see if bit X is set, failing if X is out of bounds
+    fn try_has_x_bit(&self, position: usize) -> Result<bool, BitError> {
+        self.check_bit_in_bounds(position)?;
+        Ok(self.has_x_bit(position))
+    }
+    
+    /// Checks to see if the requested bit position is in bounds, returning an error if it is not
+    fn check_bit_in_bounds(&self, position: usize) -> Result<(), BitError> {
+        if self.is_bit_in_bounds(position) {
+            Ok(())
+        } else {
+            Err(BitError::PositionOutOfBounds { position, number_of_bits: self.number_of_bits() })
+        }
     }
     
     /// Checks to see if the most signifigant bit is set
     fn has_most_signifigant_bit(&self) -> bool {
-        self.has_x_bit(self.number_of_bits() - 1) //8 bits? position 7.
+        self.number_of_bits().checked_sub(1).is_some_and(|msb| self.has_x_bit(msb)) //8 bits? position 7.
     }
     
 }
 
+/// Types whose number of bits is known at compile time
+///
+/// Primitive integers also have an inherent `BITS` constant of type `u32`, so use
+/// `<u8 as BitWidth>::BITS` or `number_of_bits::<u8>()` to get this one.
+pub trait BitWidth: BitInformation {
+
+    /// The number of bits contained in this type
+    const BITS: usize;
+
+}
+
+/// Gets the number of bits contained in a type, usable in const contexts
+pub const fn number_of_bits<T: BitWidth>() -> usize {
+    T::BITS
+}
+
+/// Checks to see if the requested bit position is in bounds for a type, usable in const contexts
+pub const fn is_bit_in_bounds<T: BitWidth>(position: usize) -> bool {
+    position < T::BITS
+}
+
+impl BitWidth for u8 {
+    const BITS: usize = 8;
+}
+
 impl BitInformation for u8 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        8
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b00000001 << position)) != 0;
+            (self & (0b00000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for i8 {
+    const BITS: usize = 8;
+}
+
 impl BitInformation for i8 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        8
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b00000001 << position)) != 0;
+            (self & (0b00000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for u16 {
+    const BITS: usize = 16;
+}
+
 impl BitInformation for u16 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        16
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b0000000000000001 << position)) != 0;
+            (self & (0b0000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for i16 {
+    const BITS: usize = 16;
+}
+
 impl BitInformation for i16 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        16
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b0000000000000001 << position)) != 0;
+            (self & (0b0000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for u32 {
+    const BITS: usize = 32;
+}
+
 impl BitInformation for u32 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        32
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b00000000000000000000000000000001 << position)) != 0;
+            (self & (0b00000000000000000000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for i32 {
+    const BITS: usize = 32;
+}
+
 impl BitInformation for i32 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        32
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b00000000000000000000000000000001 << position)) != 0;
+            (self & (0b00000000000000000000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for u64 {
+    const BITS: usize = 64;
+}
+
 impl BitInformation for u64 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        64
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0;
+            (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for i64 {
+    const BITS: usize = 64;
+}
+
 impl BitInformation for i64 {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        64
+        <Self as BitWidth>::BITS
+    }
+    
+    fn has_x_bit(&self, position: usize) -> bool {
+        if self.is_bit_in_bounds(position) {
+            (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0
+        } else {
+            false //Huh
+        }
+    }
+
+}
+
+impl BitWidth for u128 {
+    const BITS: usize = 128;
+}
+
+impl BitInformation for u128 {
+
+    /// Gets the number of bits contained in this type
+    fn number_of_bits(&self) -> usize {
+        <Self as BitWidth>::BITS
+    }
+    
+    fn has_x_bit(&self, position: usize) -> bool {
+        if self.is_bit_in_bounds(position) {
+            (self & (0b00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001 << position)) != 0
+        } else {
+            false //Huh
+        }
+    }
+
+}
+
+impl BitWidth for i128 {
+    const BITS: usize = 128;
+}
+
+impl BitInformation for i128 {
+
+    /// Gets the number of bits contained in this type
+    fn number_of_bits(&self) -> usize {
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0;
+            (self & (0b00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for usize {
+    const BITS: usize = ::core::mem::size_of::<usize>() * 8;
+}
+
 impl BitInformation for usize {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        (self.count_ones() + self.count_zeros()) as usize
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0;
+            (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
+impl BitWidth for isize {
+    const BITS: usize = ::core::mem::size_of::<isize>() * 8;
+}
+
 impl BitInformation for isize {
 
     /// Gets the number of bits contained in this type
     fn number_of_bits(&self) -> usize {
-        (self.count_ones() + self.count_zeros()) as usize
+        <Self as BitWidth>::BITS
     }
     
     fn has_x_bit(&self, position: usize) -> bool {
         if self.is_bit_in_bounds(position) {
-            return (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0;
+            (self & (0b0000000000000000000000000000000000000000000000000000000000000001 << position)) != 0
         } else {
-            return false; //Huh
+            false //Huh
         }
     }
 
 }
 
-#[allow(overflowing_literals)] //Just for tests, don't do this in real life, kids!
+#[allow(overflowing_literals, clippy::bool_comparison)] //Just for tests, don't do this in real life, kids!
 #[cfg(test)]
 mod tests {
 
@@ -214,6 +414,19 @@ mod tests {
     fn test_bit_in_bounds_i8() {
         assert!(25i8.is_bit_in_bounds(1));
         assert!(25i8.is_bit_in_bounds(9) == false);
+        assert!(25i8.is_bit_in_bounds(0));
+        assert!(25i8.is_bit_in_bounds(7));
+        assert!(25i8.is_bit_in_bounds(8) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_i8() {
+        let num: i8 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(7));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 8, number_of_bits: 8 }), num.try_has_x_bit(8));
+        assert!(num.has_x_bit(8) == false);
     }
     
     #[test]
@@ -246,6 +459,19 @@ mod tests {
     fn test_bit_in_bounds_u8() {
         assert!(25u8.is_bit_in_bounds(1));
         assert!(25u8.is_bit_in_bounds(9) == false);
+        assert!(25u8.is_bit_in_bounds(0));
+        assert!(25u8.is_bit_in_bounds(7));
+        assert!(25u8.is_bit_in_bounds(8) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_u8() {
+        let num: u8 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(7));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 8, number_of_bits: 8 }), num.try_has_x_bit(8));
+        assert!(num.has_x_bit(8) == false);
     }
     
     #[test]
@@ -278,6 +504,19 @@ mod tests {
     fn test_bit_in_bounds_u16() {
         assert!(25u16.is_bit_in_bounds(12));
         assert!(25u16.is_bit_in_bounds(18) == false);
+        assert!(25u16.is_bit_in_bounds(0));
+        assert!(25u16.is_bit_in_bounds(15));
+        assert!(25u16.is_bit_in_bounds(16) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_u16() {
+        let num: u16 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(15));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 16, number_of_bits: 16 }), num.try_has_x_bit(16));
+        assert!(num.has_x_bit(16) == false);
     }
     
     #[test]
@@ -318,6 +557,19 @@ mod tests {
     fn test_bit_in_bounds_i16() {
         assert!(25i16.is_bit_in_bounds(12));
         assert!(25i16.is_bit_in_bounds(18) == false);
+        assert!(25i16.is_bit_in_bounds(0));
+        assert!(25i16.is_bit_in_bounds(15));
+        assert!(25i16.is_bit_in_bounds(16) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_i16() {
+        let num: i16 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(15));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 16, number_of_bits: 16 }), num.try_has_x_bit(16));
+        assert!(num.has_x_bit(16) == false);
     }
     
     #[test]
@@ -358,6 +610,19 @@ mod tests {
     fn test_bit_in_bounds_u32() {
         assert!(25u32.is_bit_in_bounds(30));
         assert!(25u32.is_bit_in_bounds(57) == false);
+        assert!(25u32.is_bit_in_bounds(0));
+        assert!(25u32.is_bit_in_bounds(31));
+        assert!(25u32.is_bit_in_bounds(32) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_u32() {
+        let num: u32 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(31));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 32, number_of_bits: 32 }), num.try_has_x_bit(32));
+        assert!(num.has_x_bit(32) == false);
     }
     
     #[test]
@@ -414,6 +679,19 @@ mod tests {
     fn test_bit_in_bounds_i32() {
         assert!(25i32.is_bit_in_bounds(30));
         assert!(25i32.is_bit_in_bounds(57) == false);
+        assert!(25i32.is_bit_in_bounds(0));
+        assert!(25i32.is_bit_in_bounds(31));
+        assert!(25i32.is_bit_in_bounds(32) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_i32() {
+        let num: i32 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(31));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 32, number_of_bits: 32 }), num.try_has_x_bit(32));
+        assert!(num.has_x_bit(32) == false);
     }
     
     #[test]
@@ -470,6 +748,19 @@ mod tests {
     fn test_bit_in_bounds_u64() {
         assert!(25u64.is_bit_in_bounds(60));
         assert!(25u64.is_bit_in_bounds(69) == false);
+        assert!(25u64.is_bit_in_bounds(0));
+        assert!(25u64.is_bit_in_bounds(63));
+        assert!(25u64.is_bit_in_bounds(64) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_u64() {
+        let num: u64 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(63));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 64, number_of_bits: 64 }), num.try_has_x_bit(64));
+        assert!(num.has_x_bit(64) == false);
     }
     
     #[test]
@@ -558,6 +849,19 @@ mod tests {
     fn test_bit_in_bounds_i64() {
         assert!(25i64.is_bit_in_bounds(60));
         assert!(25i64.is_bit_in_bounds(69) == false);
+        assert!(25i64.is_bit_in_bounds(0));
+        assert!(25i64.is_bit_in_bounds(63));
+        assert!(25i64.is_bit_in_bounds(64) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_i64() {
+        let num: i64 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(63));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 64, number_of_bits: 64 }), num.try_has_x_bit(64));
+        assert!(num.has_x_bit(64) == false);
     }
     
     #[test]
@@ -630,4 +934,383 @@ mod tests {
         assert!(num.has_x_bit(63) == true);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_number_of_bits_u128() {
+        assert_eq!(128, 1u128.number_of_bits());
+    }
+    
+    #[test]
+    fn test_most_signifigant_bit_u128() {
+        assert!(1u128.has_most_signifigant_bit() == false);
+        assert!(0b10101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010u128.has_most_signifigant_bit() == true);
+        assert!(0b10110000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000u128.has_most_signifigant_bit() == true);
+    }
+    
+    #[test]
+    fn test_bit_in_bounds_u128() {
+        assert!(25u128.is_bit_in_bounds(120));
+        assert!(25u128.is_bit_in_bounds(130) == false);
+        assert!(25u128.is_bit_in_bounds(0));
+        assert!(25u128.is_bit_in_bounds(127));
+        assert!(25u128.is_bit_in_bounds(128) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_u128() {
+        let num: u128 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(127));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 128, number_of_bits: 128 }), num.try_has_x_bit(128));
+        assert!(num.has_x_bit(128) == false);
+    }
+    
+    #[test]
+    fn test_has_x_bit_u128() {
+        let num: u128 = 0b10101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010u128;
+        
+        assert!(num.has_x_bit(0) == false);
+        assert!(num.has_x_bit(1) == true);
+        assert!(num.has_x_bit(2) == false);
+        assert!(num.has_x_bit(3) == true);
+        assert!(num.has_x_bit(4) == false);
+        assert!(num.has_x_bit(5) == true);
+        assert!(num.has_x_bit(6) == false);
+        assert!(num.has_x_bit(7) == true);
+        assert!(num.has_x_bit(8) == false);
+        assert!(num.has_x_bit(9) == true);
+        assert!(num.has_x_bit(10) == false);
+        assert!(num.has_x_bit(11) == true);
+        assert!(num.has_x_bit(12) == false);
+        assert!(num.has_x_bit(13) == true);
+        assert!(num.has_x_bit(14) == false);
+        assert!(num.has_x_bit(15) == true);
+        assert!(num.has_x_bit(16) == false);
+        assert!(num.has_x_bit(17) == true);
+        assert!(num.has_x_bit(18) == false);
+        assert!(num.has_x_bit(19) == true);
+        assert!(num.has_x_bit(20) == false);
+        assert!(num.has_x_bit(21) == true);
+        assert!(num.has_x_bit(22) == false);
+        assert!(num.has_x_bit(23) == true);
+        assert!(num.has_x_bit(24) == false);
+        assert!(num.has_x_bit(25) == true);
+        assert!(num.has_x_bit(26) == false);
+        assert!(num.has_x_bit(27) == true);
+        assert!(num.has_x_bit(28) == false);
+        assert!(num.has_x_bit(29) == true);
+        assert!(num.has_x_bit(30) == false);
+        assert!(num.has_x_bit(31) == true);
+        assert!(num.has_x_bit(32) == false);
+        assert!(num.has_x_bit(33) == true);
+        assert!(num.has_x_bit(34) == false);
+        assert!(num.has_x_bit(35) == true);
+        assert!(num.has_x_bit(36) == false);
+        assert!(num.has_x_bit(37) == true);
+        assert!(num.has_x_bit(38) == false);
+        assert!(num.has_x_bit(39) == true);
+        assert!(num.has_x_bit(40) == false);
+        assert!(num.has_x_bit(41) == true);
+        assert!(num.has_x_bit(42) == false);
+        assert!(num.has_x_bit(43) == true);
+        assert!(num.has_x_bit(44) == false);
+        assert!(num.has_x_bit(45) == true);
+        assert!(num.has_x_bit(46) == false);
+        assert!(num.has_x_bit(47) == true);
+        assert!(num.has_x_bit(48) == false);
+        assert!(num.has_x_bit(49) == true);
+        assert!(num.has_x_bit(50) == false);
+        assert!(num.has_x_bit(51) == true);
+        assert!(num.has_x_bit(52) == false);
+        assert!(num.has_x_bit(53) == true);
+        assert!(num.has_x_bit(54) == false);
+        assert!(num.has_x_bit(55) == true);
+        assert!(num.has_x_bit(56) == false);
+        assert!(num.has_x_bit(57) == true);
+        assert!(num.has_x_bit(58) == false);
+        assert!(num.has_x_bit(59) == true);
+        assert!(num.has_x_bit(60) == false);
+        assert!(num.has_x_bit(61) == true);
+        assert!(num.has_x_bit(62) == false);
+        assert!(num.has_x_bit(63) == true);
+        assert!(num.has_x_bit(64) == false);
+        assert!(num.has_x_bit(65) == true);
+        assert!(num.has_x_bit(66) == false);
+        assert!(num.has_x_bit(67) == true);
+        assert!(num.has_x_bit(68) == false);
+        assert!(num.has_x_bit(69) == true);
+        assert!(num.has_x_bit(70) == false);
+        assert!(num.has_x_bit(71) == true);
+        assert!(num.has_x_bit(72) == false);
+        assert!(num.has_x_bit(73) == true);
+        assert!(num.has_x_bit(74) == false);
+        assert!(num.has_x_bit(75) == true);
+        assert!(num.has_x_bit(76) == false);
+        assert!(num.has_x_bit(77) == true);
+        assert!(num.has_x_bit(78) == false);
+        assert!(num.has_x_bit(79) == true);
+        assert!(num.has_x_bit(80) == false);
+        assert!(num.has_x_bit(81) == true);
+        assert!(num.has_x_bit(82) == false);
+        assert!(num.has_x_bit(83) == true);
+        assert!(num.has_x_bit(84) == false);
+        assert!(num.has_x_bit(85) == true);
+        assert!(num.has_x_bit(86) == false);
+        assert!(num.has_x_bit(87) == true);
+        assert!(num.has_x_bit(88) == false);
+        assert!(num.has_x_bit(89) == true);
+        assert!(num.has_x_bit(90) == false);
+        assert!(num.has_x_bit(91) == true);
+        assert!(num.has_x_bit(92) == false);
+        assert!(num.has_x_bit(93) == true);
+        assert!(num.has_x_bit(94) == false);
+        assert!(num.has_x_bit(95) == true);
+        assert!(num.has_x_bit(96) == false);
+        assert!(num.has_x_bit(97) == true);
+        assert!(num.has_x_bit(98) == false);
+        assert!(num.has_x_bit(99) == true);
+        assert!(num.has_x_bit(100) == false);
+        assert!(num.has_x_bit(101) == true);
+        assert!(num.has_x_bit(102) == false);
+        assert!(num.has_x_bit(103) == true);
+        assert!(num.has_x_bit(104) == false);
+        assert!(num.has_x_bit(105) == true);
+        assert!(num.has_x_bit(106) == false);
+        assert!(num.has_x_bit(107) == true);
+        assert!(num.has_x_bit(108) == false);
+        assert!(num.has_x_bit(109) == true);
+        assert!(num.has_x_bit(110) == false);
+        assert!(num.has_x_bit(111) == true);
+        assert!(num.has_x_bit(112) == false);
+        assert!(num.has_x_bit(113) == true);
+        assert!(num.has_x_bit(114) == false);
+        assert!(num.has_x_bit(115) == true);
+        assert!(num.has_x_bit(116) == false);
+        assert!(num.has_x_bit(117) == true);
+        assert!(num.has_x_bit(118) == false);
+        assert!(num.has_x_bit(119) == true);
+        assert!(num.has_x_bit(120) == false);
+        assert!(num.has_x_bit(121) == true);
+        assert!(num.has_x_bit(122) == false);
+        assert!(num.has_x_bit(123) == true);
+        assert!(num.has_x_bit(124) == false);
+        assert!(num.has_x_bit(125) == true);
+        assert!(num.has_x_bit(126) == false);
+        assert!(num.has_x_bit(127) == true);
+    }
+
+    #[test]
+    fn test_number_of_bits_i128() {
+        assert_eq!(128, 1i128.number_of_bits());
+    }
+    
+    #[test]
+    fn test_most_signifigant_bit_i128() {
+        assert!(1i128.has_most_signifigant_bit() == false);
+        assert!(0b10101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010i128.has_most_signifigant_bit() == true);
+        assert!(0b10110000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000i128.has_most_signifigant_bit() == true);
+    }
+    
+    #[test]
+    fn test_bit_in_bounds_i128() {
+        assert!(25i128.is_bit_in_bounds(120));
+        assert!(25i128.is_bit_in_bounds(130) == false);
+        assert!(25i128.is_bit_in_bounds(0));
+        assert!(25i128.is_bit_in_bounds(127));
+        assert!(25i128.is_bit_in_bounds(128) == false);
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_i128() {
+        let num: i128 = !0;
+        
+        assert_eq!(Ok(true), num.try_has_x_bit(0));
+        assert_eq!(Ok(true), num.try_has_x_bit(127));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 128, number_of_bits: 128 }), num.try_has_x_bit(128));
+        assert!(num.has_x_bit(128) == false);
+    }
+    
+    #[test]
+    fn test_has_x_bit_i128() {
+        let num: i128 = 0b10101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010i128;
+        
+        assert!(num.has_x_bit(0) == false);
+        assert!(num.has_x_bit(1) == true);
+        assert!(num.has_x_bit(2) == false);
+        assert!(num.has_x_bit(3) == true);
+        assert!(num.has_x_bit(4) == false);
+        assert!(num.has_x_bit(5) == true);
+        assert!(num.has_x_bit(6) == false);
+        assert!(num.has_x_bit(7) == true);
+        assert!(num.has_x_bit(8) == false);
+        assert!(num.has_x_bit(9) == true);
+        assert!(num.has_x_bit(10) == false);
+        assert!(num.has_x_bit(11) == true);
+        assert!(num.has_x_bit(12) == false);
+        assert!(num.has_x_bit(13) == true);
+        assert!(num.has_x_bit(14) == false);
+        assert!(num.has_x_bit(15) == true);
+        assert!(num.has_x_bit(16) == false);
+        assert!(num.has_x_bit(17) == true);
+        assert!(num.has_x_bit(18) == false);
+        assert!(num.has_x_bit(19) == true);
+        assert!(num.has_x_bit(20) == false);
+        assert!(num.has_x_bit(21) == true);
+        assert!(num.has_x_bit(22) == false);
+        assert!(num.has_x_bit(23) == true);
+        assert!(num.has_x_bit(24) == false);
+        assert!(num.has_x_bit(25) == true);
+        assert!(num.has_x_bit(26) == false);
+        assert!(num.has_x_bit(27) == true);
+        assert!(num.has_x_bit(28) == false);
+        assert!(num.has_x_bit(29) == true);
+        assert!(num.has_x_bit(30) == false);
+        assert!(num.has_x_bit(31) == true);
+        assert!(num.has_x_bit(32) == false);
+        assert!(num.has_x_bit(33) == true);
+        assert!(num.has_x_bit(34) == false);
+        assert!(num.has_x_bit(35) == true);
+        assert!(num.has_x_bit(36) == false);
+        assert!(num.has_x_bit(37) == true);
+        assert!(num.has_x_bit(38) == false);
+        assert!(num.has_x_bit(39) == true);
+        assert!(num.has_x_bit(40) == false);
+        assert!(num.has_x_bit(41) == true);
+        assert!(num.has_x_bit(42) == false);
+        assert!(num.has_x_bit(43) == true);
+        assert!(num.has_x_bit(44) == false);
+        assert!(num.has_x_bit(45) == true);
+        assert!(num.has_x_bit(46) == false);
+        assert!(num.has_x_bit(47) == true);
+        assert!(num.has_x_bit(48) == false);
+        assert!(num.has_x_bit(49) == true);
+        assert!(num.has_x_bit(50) == false);
+        assert!(num.has_x_bit(51) == true);
+        assert!(num.has_x_bit(52) == false);
+        assert!(num.has_x_bit(53) == true);
+        assert!(num.has_x_bit(54) == false);
+        assert!(num.has_x_bit(55) == true);
+        assert!(num.has_x_bit(56) == false);
+        assert!(num.has_x_bit(57) == true);
+        assert!(num.has_x_bit(58) == false);
+        assert!(num.has_x_bit(59) == true);
+        assert!(num.has_x_bit(60) == false);
+        assert!(num.has_x_bit(61) == true);
+        assert!(num.has_x_bit(62) == false);
+        assert!(num.has_x_bit(63) == true);
+        assert!(num.has_x_bit(64) == false);
+        assert!(num.has_x_bit(65) == true);
+        assert!(num.has_x_bit(66) == false);
+        assert!(num.has_x_bit(67) == true);
+        assert!(num.has_x_bit(68) == false);
+        assert!(num.has_x_bit(69) == true);
+        assert!(num.has_x_bit(70) == false);
+        assert!(num.has_x_bit(71) == true);
+        assert!(num.has_x_bit(72) == false);
+        assert!(num.has_x_bit(73) == true);
+        assert!(num.has_x_bit(74) == false);
+        assert!(num.has_x_bit(75) == true);
+        assert!(num.has_x_bit(76) == false);
+        assert!(num.has_x_bit(77) == true);
+        assert!(num.has_x_bit(78) == false);
+        assert!(num.has_x_bit(79) == true);
+        assert!(num.has_x_bit(80) == false);
+        assert!(num.has_x_bit(81) == true);
+        assert!(num.has_x_bit(82) == false);
+        assert!(num.has_x_bit(83) == true);
+        assert!(num.has_x_bit(84) == false);
+        assert!(num.has_x_bit(85) == true);
+        assert!(num.has_x_bit(86) == false);
+        assert!(num.has_x_bit(87) == true);
+        assert!(num.has_x_bit(88) == false);
+        assert!(num.has_x_bit(89) == true);
+        assert!(num.has_x_bit(90) == false);
+        assert!(num.has_x_bit(91) == true);
+        assert!(num.has_x_bit(92) == false);
+        assert!(num.has_x_bit(93) == true);
+        assert!(num.has_x_bit(94) == false);
+        assert!(num.has_x_bit(95) == true);
+        assert!(num.has_x_bit(96) == false);
+        assert!(num.has_x_bit(97) == true);
+        assert!(num.has_x_bit(98) == false);
+        assert!(num.has_x_bit(99) == true);
+        assert!(num.has_x_bit(100) == false);
+        assert!(num.has_x_bit(101) == true);
+        assert!(num.has_x_bit(102) == false);
+        assert!(num.has_x_bit(103) == true);
+        assert!(num.has_x_bit(104) == false);
+        assert!(num.has_x_bit(105) == true);
+        assert!(num.has_x_bit(106) == false);
+        assert!(num.has_x_bit(107) == true);
+        assert!(num.has_x_bit(108) == false);
+        assert!(num.has_x_bit(109) == true);
+        assert!(num.has_x_bit(110) == false);
+        assert!(num.has_x_bit(111) == true);
+        assert!(num.has_x_bit(112) == false);
+        assert!(num.has_x_bit(113) == true);
+        assert!(num.has_x_bit(114) == false);
+        assert!(num.has_x_bit(115) == true);
+        assert!(num.has_x_bit(116) == false);
+        assert!(num.has_x_bit(117) == true);
+        assert!(num.has_x_bit(118) == false);
+        assert!(num.has_x_bit(119) == true);
+        assert!(num.has_x_bit(120) == false);
+        assert!(num.has_x_bit(121) == true);
+        assert!(num.has_x_bit(122) == false);
+        assert!(num.has_x_bit(123) == true);
+        assert!(num.has_x_bit(124) == false);
+        assert!(num.has_x_bit(125) == true);
+        assert!(num.has_x_bit(126) == false);
+        assert!(num.has_x_bit(127) == true);
+    }
+
+    #[test]
+    fn test_bit_width_consts() {
+        const PACKED: [u8; number_of_bits::<u32>() / 8] = [0; 4];
+        
+        assert_eq!(4, PACKED.len());
+        assert_eq!(8, <i8 as BitWidth>::BITS);
+        assert_eq!(16, <u16 as BitWidth>::BITS);
+        assert_eq!(64, <i64 as BitWidth>::BITS);
+        assert_eq!(128, number_of_bits::<u128>());
+        assert_eq!(number_of_bits::<usize>(), 0usize.number_of_bits());
+        assert_eq!(number_of_bits::<isize>(), 0isize.number_of_bits());
+        assert!(is_bit_in_bounds::<u32>(31));
+        assert!(is_bit_in_bounds::<u32>(32) == false);
+    }
+
+    #[test]
+    fn test_number_of_bits_usize() {
+        assert_eq!(::core::mem::size_of::<usize>() * 8, 1usize.number_of_bits());
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_usize() {
+        let num = !0usize;
+        let bits = num.number_of_bits();
+        
+        assert!(num.is_bit_in_bounds(bits - 1));
+        assert!(num.is_bit_in_bounds(bits) == false);
+        assert_eq!(Ok(true), num.try_has_x_bit(bits - 1));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: bits, number_of_bits: bits }), num.try_has_x_bit(bits));
+        assert!(num.has_x_bit(bits) == false);
+    }
+
+    #[test]
+    fn test_number_of_bits_isize() {
+        assert_eq!(::core::mem::size_of::<isize>() * 8, 1isize.number_of_bits());
+    }
+    
+    #[test]
+    fn test_try_has_x_bit_isize() {
+        let num = -1isize;
+        let bits = num.number_of_bits();
+        
+        assert!(num.is_bit_in_bounds(bits - 1));
+        assert!(num.is_bit_in_bounds(bits) == false);
+        assert_eq!(Ok(true), num.try_has_x_bit(bits - 1));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: bits, number_of_bits: bits }), num.try_has_x_bit(bits));
+        assert!(num.has_x_bit(bits) == false);
+    }
+
+}
@@ -0,0 +1,340 @@
+//! Changing individual bits
+//!
+//! Positions that are out of bounds are ignored, just like `has_x_bit` returns false for them. The
+//! `try_` variants report them as errors instead.
+
+use {BitError, BitInformation};
+
+/// The mutating counterpart to BitInformation
+pub trait BitManipulation: BitInformation {
+
+    /// Sets bit X
+    fn set_x_bit(&mut self, position: usize);
+
+    /// Clears bit X
+    fn clear_x_bit(&mut self, position: usize);
+
+    /// Flips bit X
+    fn toggle_x_bit(&mut self, position: usize) {
+        if self.has_x_bit(position) {
+            self.clear_x_bit(position);
+        } else {
+            self.set_x_bit(position);
+        }
+    }
+
+    /// Sets bit X if `value` is true, otherwise clears it
+    fn assign_x_bit(&mut self, position: usize, value: bool) {
+        if value {
+            self.set_x_bit(position);
+        } else {
+            self.clear_x_bit(position);
+        }
+    }
+
+    /// Sets bit X, failing if X is out of bounds
+    fn try_set_x_bit(&mut self, position: usize) -> Result<(), BitError> {
+        self.check_bit_in_bounds(position)?;
+        self.set_x_bit(position);
+        Ok(())
+    }
+
+    /// Clears bit X, failing if X is out of bounds
+    fn try_clear_x_bit(&mut self, position: usize) -> Result<(), BitError> {
+        self.check_bit_in_bounds(position)?;
+        self.clear_x_bit(position);
+        Ok(())
+    }
+
+    /// Flips bit X, failing if X is out of bounds
+    fn try_toggle_x_bit(&mut self, position: usize) -> Result<(), BitError> {
+        self.check_bit_in_bounds(position)?;
+        self.toggle_x_bit(position);
+        Ok(())
+    }
+
+    /// Sets bit X if `value` is true, otherwise clears it, failing if X is out of bounds
+    fn try_assign_x_bit(&mut self, position: usize, value: bool) -> Result<(), BitError> {
+        self.check_bit_in_bounds(position)?;
+        self.assign_x_bit(position, value);
+        Ok(())
+    }
+
+    /// Gets a copy of this value with bit X set
+    fn with_x_bit(mut self, position: usize) -> Self where Self: Sized {
+        self.set_x_bit(position);
+        self
+    }
+
+    /// Gets a copy of this value with bit X cleared
+    fn without_x_bit(mut self, position: usize) -> Self where Self: Sized {
+        self.clear_x_bit(position);
+        self
+    }
+
+}
+
+macro_rules! impl_bit_manipulation {
+    ($($t:ty),*) => {
+        $(
+            impl BitManipulation for $t {
+
+                fn set_x_bit(&mut self, position: usize) {
+                    if self.is_bit_in_bounds(position) {
+                        *self |= 1 << position;
+                    }
+                }
+
+                fn clear_x_bit(&mut self, position: usize) {
+                    if self.is_bit_in_bounds(position) {
+                        *self &= !(1 << position);
+                    }
+                }
+
+                fn toggle_x_bit(&mut self, position: usize) {
+                    if self.is_bit_in_bounds(position) {
+                        *self ^= 1 << position;
+                    }
+                }
+
+            }
+        )*
+    }
+}
+
+impl_bit_manipulation!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+#[allow(overflowing_literals)] //Just for tests, don't do this in real life, kids!
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_bit_manipulation_u8() {
+        let mut num = 0b10101010u8;
+
+        num.set_x_bit(0);
+        assert_eq!(0b10101011u8, num);
+        num.clear_x_bit(7);
+        assert_eq!(0b00101011u8, num);
+        num.toggle_x_bit(2);
+        assert_eq!(0b00101111u8, num);
+        num.assign_x_bit(2, false);
+        num.assign_x_bit(6, true);
+        assert_eq!(0b01101011u8, num);
+        assert_eq!(0b11101011u8, num.with_x_bit(7));
+        assert_eq!(0b01101010u8, num.without_x_bit(0));
+    }
+
+    #[test]
+    fn test_bit_manipulation_i8() {
+        let mut num = 0b10101010i8;
+
+        num.set_x_bit(0);
+        assert_eq!(0b10101011i8, num);
+        num.clear_x_bit(7);
+        assert_eq!(0b00101011i8, num);
+        num.toggle_x_bit(7);
+        assert_eq!(0b10101011i8, num);
+        assert!(num < 0);
+        num.assign_x_bit(7, false);
+        assert_eq!(0b00101011i8, num);
+        assert_eq!(0b10101011i8, num.with_x_bit(7));
+        assert_eq!(0b00101001i8, num.without_x_bit(1));
+    }
+
+    #[test]
+    fn test_bit_manipulation_u16() {
+        let mut num = 0u16;
+
+        num.set_x_bit(15);
+        num.set_x_bit(3);
+        assert_eq!(0b1000000000001000u16, num);
+        num.toggle_x_bit(15);
+        num.toggle_x_bit(8);
+        assert_eq!(0b0000000100001000u16, num);
+        num.clear_x_bit(3);
+        num.assign_x_bit(0, true);
+        assert_eq!(0b0000000100000001u16, num);
+        assert_eq!(0b1000000100000001u16, num.with_x_bit(15));
+        assert_eq!(0b0000000000000001u16, num.without_x_bit(8));
+    }
+
+    #[test]
+    fn test_bit_manipulation_i16() {
+        let mut num = -1i16;
+
+        num.clear_x_bit(15);
+        assert_eq!(i16::MAX, num);
+        num.toggle_x_bit(15);
+        assert_eq!(-1i16, num);
+        num.assign_x_bit(0, false);
+        assert_eq!(-2i16, num);
+        assert_eq!(-1i16, num.with_x_bit(0));
+        assert_eq!(0b0111111111111110i16, num.without_x_bit(15));
+    }
+
+    #[test]
+    fn test_bit_manipulation_u32() {
+        let mut num = 0xDEADBEEFu32;
+
+        num.clear_x_bit(0);
+        assert_eq!(0xDEADBEEEu32, num);
+        num.toggle_x_bit(31);
+        assert_eq!(0x5EADBEEEu32, num);
+        num.set_x_bit(4);
+        num.assign_x_bit(16, false);
+        assert_eq!(0x5EACBEFEu32, num);
+        assert_eq!(0xDEACBEFEu32, num.with_x_bit(31));
+        assert_eq!(0x5EACBEFCu32, num.without_x_bit(1));
+    }
+
+    #[test]
+    fn test_bit_manipulation_i32() {
+        let mut num = 0i32;
+
+        num.set_x_bit(31);
+        assert_eq!(i32::MIN, num);
+        num.toggle_x_bit(0);
+        assert_eq!(i32::MIN + 1, num);
+        num.assign_x_bit(31, false);
+        assert_eq!(1i32, num);
+        assert_eq!(3i32, num.with_x_bit(1));
+        assert_eq!(0i32, num.without_x_bit(0));
+    }
+
+    #[test]
+    fn test_bit_manipulation_u64() {
+        let mut num = 0u64;
+
+        num.set_x_bit(63);
+        num.set_x_bit(32);
+        assert_eq!(0x8000000100000000u64, num);
+        num.toggle_x_bit(63);
+        num.clear_x_bit(32);
+        assert_eq!(0u64, num);
+        num.assign_x_bit(40, true);
+        assert_eq!(1u64 << 40, num);
+        assert_eq!((1u64 << 40) | 1, num.with_x_bit(0));
+        assert_eq!(0u64, num.without_x_bit(40));
+    }
+
+    #[test]
+    fn test_bit_manipulation_i64() {
+        let mut num = -1i64;
+
+        num.clear_x_bit(63);
+        assert_eq!(i64::MAX, num);
+        num.toggle_x_bit(62);
+        assert_eq!(0x3FFFFFFFFFFFFFFFi64, num);
+        num.assign_x_bit(63, true);
+        assert_eq!(0xBFFFFFFFFFFFFFFFi64, num);
+        assert_eq!(-1i64, num.with_x_bit(62));
+        assert_eq!(0x3FFFFFFFFFFFFFFFi64, num.without_x_bit(63));
+    }
+
+    #[test]
+    fn test_bit_manipulation_u128() {
+        let mut num = 0u128;
+
+        num.set_x_bit(127);
+        num.set_x_bit(64);
+        assert_eq!((1u128 << 127) | (1u128 << 64), num);
+        num.toggle_x_bit(127);
+        num.clear_x_bit(64);
+        assert_eq!(0u128, num);
+        num.assign_x_bit(100, true);
+        assert_eq!(1u128 << 100, num);
+        assert_eq!((1u128 << 100) | 1, num.with_x_bit(0));
+        assert_eq!(0u128, num.without_x_bit(100));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 128, number_of_bits: 128 }), num.try_set_x_bit(128));
+    }
+
+    #[test]
+    fn test_bit_manipulation_i128() {
+        let mut num = -1i128;
+
+        num.clear_x_bit(127);
+        assert_eq!(i128::MAX, num);
+        num.toggle_x_bit(127);
+        assert_eq!(-1i128, num);
+        num.assign_x_bit(0, false);
+        assert_eq!(-2i128, num);
+        assert_eq!(-1i128, num.with_x_bit(0));
+        assert_eq!(i128::MAX - 1, num.without_x_bit(127));
+    }
+
+    #[test]
+    fn test_bit_manipulation_usize() {
+        let mut num = 0usize;
+        let top = num.number_of_bits() - 1;
+
+        num.set_x_bit(top);
+        assert_eq!(1usize << top, num);
+        num.toggle_x_bit(top);
+        num.assign_x_bit(5, true);
+        assert_eq!(32usize, num);
+        assert_eq!(33usize, num.with_x_bit(0));
+        assert_eq!(0usize, num.without_x_bit(5));
+    }
+
+    #[test]
+    fn test_bit_manipulation_isize() {
+        let mut num = 0isize;
+        let top = num.number_of_bits() - 1;
+
+        num.set_x_bit(top);
+        assert_eq!(isize::MIN, num);
+        num.clear_x_bit(top);
+        num.toggle_x_bit(1);
+        assert_eq!(2isize, num);
+        assert_eq!(isize::MIN | 2, num.with_x_bit(top));
+        assert_eq!(0isize, num.without_x_bit(1));
+    }
+
+    #[test]
+    fn test_bit_manipulation_out_of_bounds() {
+        let mut num = 0b1010u8;
+
+        num.set_x_bit(100);
+        num.clear_x_bit(100);
+        num.toggle_x_bit(100);
+        num.assign_x_bit(100, true);
+        assert_eq!(0b1010u8, num);
+    }
+
+    #[test]
+    fn test_bit_manipulation_boundary() {
+        let mut small = 0u8;
+        let mut large = 0i64;
+        let mut native = 0usize;
+        let top = native.number_of_bits() - 1;
+
+        small.set_x_bit(8);
+        large.set_x_bit(64);
+        native.set_x_bit(top + 1);
+        assert_eq!((0u8, 0i64, 0usize), (small, large, native));
+
+        assert_eq!(Ok(()), small.try_set_x_bit(7));
+        assert_eq!(Ok(()), large.try_toggle_x_bit(63));
+        assert_eq!(Ok(()), native.try_assign_x_bit(top, true));
+        assert_eq!((0x80u8, i64::MIN, 1usize << top), (small, large, native));
+
+        assert_eq!(Ok(()), small.try_clear_x_bit(7));
+        assert_eq!(0u8, small);
+    }
+
+    #[test]
+    fn test_try_bit_manipulation_errors() {
+        let mut num = 0x0Fu16;
+        let error = Err(BitError::PositionOutOfBounds { position: 16, number_of_bits: 16 });
+
+        assert_eq!(error, num.try_set_x_bit(16));
+        assert_eq!(error, num.try_clear_x_bit(16));
+        assert_eq!(error, num.try_toggle_x_bit(16));
+        assert_eq!(error, num.try_assign_x_bit(16, true));
+        assert_eq!(0x0Fu16, num);
+    }
+
+}
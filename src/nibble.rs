@@ -0,0 +1,436 @@
+//! A 4-bit Nibble type
+//!
+//! Nibbles are stored in the low half of a byte. Arithmetic wraps at 4 bits, just like the
+//! primitive integers wrap at their own width.
+
+use core::fmt;
+use core::ops::{Add, AddAssign, BitAnd, BitOr, BitXor, Div, Mul, MulAssign, Not, Rem, Sub, SubAssign};
+
+use {BitInformation, BitIteration, BitManipulation, BitOrdering, BitQueries, BitWidth};
+
+/// A single Nibble (4 bits)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nibble(u8);
+
+impl Nibble {
+
+    /// The smallest value a Nibble can hold
+    pub const MIN: Nibble = Nibble(0x00);
+
+    /// The largest value a Nibble can hold
+    pub const MAX: Nibble = Nibble(0x0F);
+
+    /// Creates a Nibble, returning None if the value does not fit in 4 bits
+    pub const fn new(value: u8) -> Option<Nibble> {
+        if value <= 0x0F {
+            Some(Nibble(value))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a Nibble from the lowest 4 bits of a byte, discarding the rest
+    pub const fn from_low_bits(value: u8) -> Nibble {
+        Nibble(value & 0x0F)
+    }
+
+    /// Gets the high (most signifigant) Nibble of a byte
+    pub const fn high_of(byte: u8) -> Nibble {
+        Nibble(byte >> 4)
+    }
+
+    /// Gets the low (least signifigant) Nibble of a byte
+    pub const fn low_of(byte: u8) -> Nibble {
+        Nibble(byte & 0x0F)
+    }
+
+    /// Joins two Nibbles into a byte, with `high` in the upper 4 bits
+    pub const fn join(high: Nibble, low: Nibble) -> u8 {
+        (high.0 << 4) | low.0
+    }
+
+    /// Gets the value of this Nibble as a u8
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Adds two Nibbles, returning None if the result does not fit in 4 bits
+    pub const fn checked_add(self, other: Nibble) -> Option<Nibble> {
+        Nibble::new(self.0 + other.0)
+    }
+
+    /// Subtracts two Nibbles, returning None if the result would be negative
+    pub const fn checked_sub(self, other: Nibble) -> Option<Nibble> {
+        if self.0 >= other.0 {
+            Some(Nibble(self.0 - other.0))
+        } else {
+            None
+        }
+    }
+
+    /// Multiplies two Nibbles, returning None if the result does not fit in 4 bits
+    pub const fn checked_mul(self, other: Nibble) -> Option<Nibble> {
+        Nibble::new(self.0 * other.0)
+    }
+
+}
+
+impl From<Nibble> for u8 {
+    fn from(nibble: Nibble) -> u8 {
+        nibble.0
+    }
+}
+
+impl fmt::Display for Nibble {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for Nibble {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for Nibble {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Binary for Nibble {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+impl Add for Nibble {
+    type Output = Nibble;
+
+    fn add(self, other: Nibble) -> Nibble {
+        Nibble::from_low_bits(self.0 + other.0)
+    }
+}
+
+impl Sub for Nibble {
+    type Output = Nibble;
+
+    fn sub(self, other: Nibble) -> Nibble {
+        Nibble::from_low_bits(self.0.wrapping_sub(other.0))
+    }
+}
+
+impl Mul for Nibble {
+    type Output = Nibble;
+
+    fn mul(self, other: Nibble) -> Nibble {
+        Nibble::from_low_bits(self.0 * other.0)
+    }
+}
+
+impl Div for Nibble {
+    type Output = Nibble;
+
+    fn div(self, other: Nibble) -> Nibble {
+        Nibble(self.0 / other.0)
+    }
+}
+
+impl Rem for Nibble {
+    type Output = Nibble;
+
+    fn rem(self, other: Nibble) -> Nibble {
+        Nibble(self.0 % other.0)
+    }
+}
+
+impl AddAssign for Nibble {
+    fn add_assign(&mut self, other: Nibble) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Nibble {
+    fn sub_assign(&mut self, other: Nibble) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Nibble {
+    fn mul_assign(&mut self, other: Nibble) {
+        *self = *self * other;
+    }
+}
+
+impl Not for Nibble {
+    type Output = Nibble;
+
+    fn not(self) -> Nibble {
+        Nibble::from_low_bits(!self.0)
+    }
+}
+
+impl BitAnd for Nibble {
+    type Output = Nibble;
+
+    fn bitand(self, other: Nibble) -> Nibble {
+        Nibble(self.0 & other.0)
+    }
+}
+
+impl BitOr for Nibble {
+    type Output = Nibble;
+
+    fn bitor(self, other: Nibble) -> Nibble {
+        Nibble(self.0 | other.0)
+    }
+}
+
+impl BitXor for Nibble {
+    type Output = Nibble;
+
+    fn bitxor(self, other: Nibble) -> Nibble {
+        Nibble(self.0 ^ other.0)
+    }
+}
+
+impl BitWidth for Nibble {
+    const BITS: usize = 4;
+}
+
+impl BitInformation for Nibble {
+
+    /// Gets the number of bits contained in this type
+    fn number_of_bits(&self) -> usize {
+        Nibble::BITS
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        if self.is_bit_in_bounds(position) {
+            (self.0 & (0b0001 << position)) != 0
+        } else {
+            false //Huh
+        }
+    }
+
+}
+
+impl BitIteration for Nibble {}
+
+impl BitOrdering for Nibble {}
+
+impl BitQueries for Nibble {
+
+    fn highest_set_bit(&self) -> Option<usize> {
+        self.0.highest_set_bit()
+    }
+
+    fn lowest_set_bit(&self) -> Option<usize> {
+        self.0.lowest_set_bit()
+    }
+
+    fn is_power_of_two(&self) -> bool {
+        self.0.is_power_of_two()
+    }
+
+    fn checked_next_power_of_two(&self) -> Option<Nibble> {
+        self.0.checked_next_power_of_two().and_then(Nibble::new)
+    }
+
+    fn floor_log2(&self) -> Option<usize> {
+        self.0.floor_log2()
+    }
+
+    fn ceil_log2(&self) -> Option<usize> {
+        self.0.ceil_log2()
+    }
+
+    fn isolate_lowest_set(&self) -> Nibble {
+        Nibble(self.0.isolate_lowest_set())
+    }
+
+    fn clear_lowest_set(&self) -> Nibble {
+        Nibble(self.0.clear_lowest_set())
+    }
+
+}
+
+impl BitManipulation for Nibble {
+
+    fn set_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.0 |= 0b0001 << position;
+        }
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            self.0 &= !(0b0001 << position) & 0x0F;
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+    use BitError;
+
+    fn nibble(value: u8) -> Nibble {
+        Nibble::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_new_nibble() {
+        assert_eq!(Some(Nibble::MIN), Nibble::new(0));
+        assert_eq!(Some(Nibble::MAX), Nibble::new(15));
+        assert_eq!(None, Nibble::new(16));
+        assert_eq!(None, Nibble::new(0xFF));
+    }
+
+    #[test]
+    fn test_conversions_nibble() {
+        assert_eq!(nibble(0x0B), Nibble::from_low_bits(0xAB));
+        assert_eq!(nibble(0x0A), Nibble::high_of(0xAB));
+        assert_eq!(nibble(0x0B), Nibble::low_of(0xAB));
+        assert_eq!(0xAB, Nibble::join(nibble(0x0A), nibble(0x0B)));
+        assert_eq!(12u8, u8::from(nibble(12)));
+        assert_eq!(12, nibble(12).value());
+    }
+
+    #[test]
+    fn test_formatting_nibble() {
+        assert_eq!("11", format!("{}", nibble(11)));
+        assert_eq!("b", format!("{:x}", nibble(11)));
+        assert_eq!("B", format!("{:X}", nibble(11)));
+        assert_eq!("1011", format!("{:b}", nibble(11)));
+    }
+
+    #[test]
+    fn test_wrapping_arithmetic_nibble() {
+        assert_eq!(nibble(9), nibble(4) + nibble(5));
+        assert_eq!(nibble(2), nibble(15) + nibble(3));
+        assert_eq!(nibble(14), nibble(1) - nibble(3));
+        assert_eq!(nibble(4), nibble(6) * nibble(6));
+        assert_eq!(nibble(3), nibble(15) / nibble(5));
+        assert_eq!(nibble(1), nibble(13) % nibble(4));
+
+        let mut value = nibble(14);
+        value += nibble(3);
+        assert_eq!(nibble(1), value);
+        value -= nibble(2);
+        assert_eq!(nibble(15), value);
+        value *= nibble(2);
+        assert_eq!(nibble(14), value);
+    }
+
+    #[test]
+    fn test_checked_arithmetic_nibble() {
+        assert_eq!(Some(nibble(15)), nibble(7).checked_add(nibble(8)));
+        assert_eq!(None, nibble(8).checked_add(nibble(8)));
+        assert_eq!(Some(nibble(0)), nibble(3).checked_sub(nibble(3)));
+        assert_eq!(None, nibble(2).checked_sub(nibble(3)));
+        assert_eq!(Some(nibble(15)), nibble(5).checked_mul(nibble(3)));
+        assert_eq!(None, nibble(4).checked_mul(nibble(4)));
+    }
+
+    #[test]
+    fn test_bitwise_nibble() {
+        assert_eq!(nibble(0b0101), !nibble(0b1010));
+        assert_eq!(nibble(0b1000), nibble(0b1010) & nibble(0b1100));
+        assert_eq!(nibble(0b1110), nibble(0b1010) | nibble(0b1100));
+        assert_eq!(nibble(0b0110), nibble(0b1010) ^ nibble(0b1100));
+    }
+
+    #[test]
+    fn test_number_of_bits_nibble() {
+        assert_eq!(4, nibble(1).number_of_bits());
+        assert_eq!(4, Nibble::BITS);
+        assert_eq!(4, ::number_of_bits::<Nibble>());
+    }
+
+    #[test]
+    fn test_most_signifigant_bit_nibble() {
+        assert!(!nibble(1).has_most_signifigant_bit());
+        assert!(nibble(0b1010).has_most_signifigant_bit());
+        assert!(nibble(0b1000).has_most_signifigant_bit());
+    }
+
+    #[test]
+    fn test_bit_in_bounds_nibble() {
+        assert!(nibble(9).is_bit_in_bounds(0));
+        assert!(nibble(9).is_bit_in_bounds(3));
+        assert!(!nibble(9).is_bit_in_bounds(4));
+        assert!(!nibble(9).is_bit_in_bounds(5));
+    }
+
+    #[test]
+    fn test_try_has_x_bit_nibble() {
+        let mut num = Nibble::MAX;
+
+        assert_eq!(Ok(true), num.try_has_x_bit(3));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 4, number_of_bits: 4 }), num.try_has_x_bit(4));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 4, number_of_bits: 4 }), num.try_clear_x_bit(4));
+        assert_eq!(Ok(()), num.try_clear_x_bit(3));
+        assert_eq!(nibble(0b0111), num);
+    }
+
+    #[test]
+    fn test_has_x_bit_nibble() {
+        let num = nibble(0b1010);
+
+        assert!(!num.has_x_bit(0));
+        assert!(num.has_x_bit(1));
+        assert!(!num.has_x_bit(2));
+        assert!(num.has_x_bit(3));
+        assert!(!num.has_x_bit(4));
+    }
+
+    #[test]
+    fn test_bit_iteration_nibble() {
+        let num = nibble(0b1001);
+
+        assert_eq!(vec![true, false, false, true], num.bits().collect::<Vec<_>>());
+        assert_eq!(vec![0, 3], num.ones().collect::<Vec<_>>());
+        assert_eq!(vec![1, 2], num.zeros().collect::<Vec<_>>());
+        assert_eq!(vec![(0, 1, true), (1, 2, false), (3, 1, true)], num.runs().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bit_queries_nibble() {
+        assert_eq!(Some(3), nibble(0b1010).highest_set_bit());
+        assert_eq!(Some(1), nibble(0b1010).lowest_set_bit());
+        assert_eq!(3, nibble(0b0110).bit_length());
+        assert!(nibble(8).is_power_of_two());
+        assert!(!nibble(0).is_power_of_two());
+        assert_eq!(Some(nibble(8)), nibble(5).checked_next_power_of_two());
+        assert_eq!(None, nibble(9).checked_next_power_of_two());
+        assert_eq!(Some(2), nibble(7).floor_log2());
+        assert_eq!(Some(3), nibble(7).ceil_log2());
+        assert_eq!(nibble(0b0100), nibble(0b1100).isolate_lowest_set());
+        assert_eq!(nibble(0b1000), nibble(0b1100).clear_lowest_set());
+    }
+
+    #[test]
+    fn test_bit_manipulation_nibble() {
+        let mut num = nibble(0b1010);
+
+        num.set_x_bit(0);
+        assert_eq!(nibble(0b1011), num);
+        num.clear_x_bit(3);
+        assert_eq!(nibble(0b0011), num);
+        num.toggle_x_bit(2);
+        assert_eq!(nibble(0b0111), num);
+        num.assign_x_bit(3, true);
+        assert_eq!(Nibble::MAX, num);
+        assert_eq!(nibble(0b0111), num.without_x_bit(3));
+        assert_eq!(Nibble::MAX, nibble(0b1110).with_x_bit(0));
+
+        num.set_x_bit(4);
+        assert_eq!(Nibble::MAX, num);
+    }
+
+}
@@ -0,0 +1,550 @@
+//! Growable and borrowed sequences of Nibbles, packed two to a byte
+//!
+//! The first Nibble of each byte is its high Nibble, so the bytes `[0xAB, 0xCD]` hold the
+//! Nibbles `a, b, c, d`. This is the layout used for hex paths such as Patricia trie keys.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{Bound, Index, RangeBounds};
+
+use Nibble;
+
+/// Every Nibble, so that indexing can hand out references
+static NIBBLES: [Nibble; 16] = [
+    Nibble::from_low_bits(0x0), Nibble::from_low_bits(0x1), Nibble::from_low_bits(0x2), Nibble::from_low_bits(0x3),
+    Nibble::from_low_bits(0x4), Nibble::from_low_bits(0x5), Nibble::from_low_bits(0x6), Nibble::from_low_bits(0x7),
+    Nibble::from_low_bits(0x8), Nibble::from_low_bits(0x9), Nibble::from_low_bits(0xA), Nibble::from_low_bits(0xB),
+    Nibble::from_low_bits(0xC), Nibble::from_low_bits(0xD), Nibble::from_low_bits(0xE), Nibble::from_low_bits(0xF),
+];
+
+/// Gets Nibble X of some packed bytes
+fn nibble_of(bytes: &[u8], position: usize) -> Nibble {
+    let byte = bytes[position / 2];
+
+    if position.is_multiple_of(2) {
+        Nibble::high_of(byte)
+    } else {
+        Nibble::low_of(byte)
+    }
+}
+
+/// Turns a range of Nibbles into (start, end), panicking if it is out of bounds like slicing does
+fn resolve_range<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(start <= end, "Nibble range starts at {} but ends at {}", start, end);
+    assert!(end <= len, "Nibble range end {} is out of range for length {}", end, len);
+
+    (start, end)
+}
+
+/// A borrowed sequence of Nibbles, which may start and end halfway through a byte
+#[derive(Clone, Copy)]
+pub struct NibbleSlice<'a> {
+    bytes: &'a [u8],
+    start: usize,
+    end: usize,
+}
+
+impl<'a> NibbleSlice<'a> {
+
+    /// Creates a NibbleSlice covering every Nibble of some bytes
+    pub fn new(bytes: &'a [u8]) -> NibbleSlice<'a> {
+        NibbleSlice { bytes, start: 0, end: bytes.len() * 2 }
+    }
+
+    /// Gets the number of Nibbles in this slice
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Checks to see if this slice has no Nibbles
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Gets Nibble X, or None if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<Nibble> {
+        if index < self.len() {
+            Some(nibble_of(self.bytes, self.start + index))
+        } else {
+            None
+        }
+    }
+
+    /// Gets a sub-slice, which may start or end at an odd Nibble offset
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> NibbleSlice<'a> {
+        let (start, end) = resolve_range(range, self.len());
+
+        NibbleSlice { bytes: self.bytes, start: self.start + start, end: self.start + end }
+    }
+
+    /// Gets the number of leading Nibbles this slice shares with another
+    pub fn common_prefix_len(&self, other: &NibbleSlice) -> usize {
+        self.iter().zip(other.iter()).take_while(|&(ours, theirs)| ours == theirs).count()
+    }
+
+    /// Checks to see if this slice begins with another
+    pub fn starts_with(&self, prefix: &NibbleSlice) -> bool {
+        prefix.len() <= self.len() && self.common_prefix_len(prefix) == prefix.len()
+    }
+
+    /// Iterates over the Nibbles in this slice
+    pub fn iter(&self) -> Iter<'a> {
+        Iter { bytes: self.bytes, front: self.start, back: self.end }
+    }
+
+    /// Copies this slice into a new NibbleVec
+    pub fn to_vec(&self) -> NibbleVec {
+        self.iter().collect()
+    }
+
+}
+
+impl<'a> Index<usize> for NibbleSlice<'a> {
+    type Output = Nibble;
+
+    fn index(&self, index: usize) -> &Nibble {
+        match self.get(index) {
+            Some(nibble) => &NIBBLES[nibble.value() as usize],
+            None => panic!("Nibble index {} is out of range for length {}", index, self.len()),
+        }
+    }
+}
+
+impl<'a, 'b> PartialEq<NibbleSlice<'b>> for NibbleSlice<'a> {
+    fn eq(&self, other: &NibbleSlice<'b>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<'a> Eq for NibbleSlice<'a> {}
+
+impl<'a> PartialOrd for NibbleSlice<'a> {
+    fn partial_cmp(&self, other: &NibbleSlice<'a>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for NibbleSlice<'a> {
+    fn cmp(&self, other: &NibbleSlice<'a>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<'a> Hash for NibbleSlice<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for nibble in self.iter() {
+            nibble.hash(state);
+        }
+    }
+}
+
+impl<'a> fmt::Debug for NibbleSlice<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("NibbleSlice(")?;
+        for nibble in self.iter() {
+            write!(f, "{:x}", nibble)?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl<'a> IntoIterator for NibbleSlice<'a> {
+    type Item = Nibble;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the Nibbles of a NibbleSlice or NibbleVec
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    bytes: &'a [u8],
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Nibble;
+
+    fn next(&mut self) -> Option<Nibble> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(nibble_of(self.bytes, self.front - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    fn next_back(&mut self) -> Option<Nibble> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(nibble_of(self.bytes, self.back))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+/// A growable sequence of Nibbles
+///
+/// When the length is odd, the unused low half of the last byte is always zero.
+#[derive(Clone, Default)]
+pub struct NibbleVec {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl NibbleVec {
+
+    /// Creates an empty NibbleVec
+    pub fn new() -> NibbleVec {
+        NibbleVec { bytes: Vec::new(), len: 0 }
+    }
+
+    /// Creates an empty NibbleVec with room for at least `capacity` Nibbles
+    pub fn with_capacity(capacity: usize) -> NibbleVec {
+        NibbleVec { bytes: Vec::with_capacity(capacity.div_ceil(2)), len: 0 }
+    }
+
+    /// Creates a NibbleVec holding every Nibble of some bytes
+    pub fn from_bytes(bytes: &[u8]) -> NibbleVec {
+        NibbleVec { bytes: bytes.to_vec(), len: bytes.len() * 2 }
+    }
+
+    /// Gets the number of Nibbles in this NibbleVec
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if this NibbleVec has no Nibbles
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets Nibble X, or None if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<Nibble> {
+        self.as_slice().get(index)
+    }
+
+    /// Replaces Nibble X, panicking if it is out of bounds
+    pub fn set(&mut self, index: usize, nibble: Nibble) {
+        assert!(index < self.len, "Nibble index {} is out of range for length {}", index, self.len);
+
+        let byte = &mut self.bytes[index / 2];
+
+        if index.is_multiple_of(2) {
+            *byte = Nibble::join(nibble, Nibble::low_of(*byte));
+        } else {
+            *byte = Nibble::join(Nibble::high_of(*byte), nibble);
+        }
+    }
+
+    /// Adds a Nibble to the end
+    pub fn push(&mut self, nibble: Nibble) {
+        if self.len.is_multiple_of(2) {
+            self.bytes.push(Nibble::join(nibble, Nibble::MIN));
+        } else {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= nibble.value();
+        }
+        self.len += 1;
+    }
+
+    /// Removes the last Nibble, or returns None if this NibbleVec is empty
+    pub fn pop(&mut self) -> Option<Nibble> {
+        let nibble = self.get(self.len.checked_sub(1)?)?;
+        self.truncate(self.len - 1);
+        Some(nibble)
+    }
+
+    /// Shortens this NibbleVec to `len` Nibbles, doing nothing if it is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.bytes.truncate(len.div_ceil(2));
+            if len % 2 == 1 {
+                let last = self.bytes.len() - 1;
+                self.bytes[last] &= 0xF0;
+            }
+            self.len = len;
+        }
+    }
+
+    /// Removes every Nibble
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.len = 0;
+    }
+
+    /// Adds every Nibble of a slice to the end
+    pub fn extend_from_slice(&mut self, nibbles: &NibbleSlice) {
+        self.extend(nibbles.iter());
+    }
+
+    /// Borrows every Nibble as a NibbleSlice
+    pub fn as_slice(&self) -> NibbleSlice<'_> {
+        NibbleSlice { bytes: &self.bytes, start: 0, end: self.len }
+    }
+
+    /// Borrows a range of Nibbles as a NibbleSlice
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> NibbleSlice<'_> {
+        self.as_slice().slice(range)
+    }
+
+    /// Gets the number of leading Nibbles this NibbleVec shares with a slice
+    pub fn common_prefix_len(&self, other: &NibbleSlice) -> usize {
+        self.as_slice().common_prefix_len(other)
+    }
+
+    /// Iterates over the Nibbles in this NibbleVec
+    pub fn iter(&self) -> Iter<'_> {
+        self.as_slice().iter()
+    }
+
+    /// Gets the packed bytes, with the last low Nibble zeroed if the length is odd
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Turns this NibbleVec into its packed bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+}
+
+impl Index<usize> for NibbleVec {
+    type Output = Nibble;
+
+    fn index(&self, index: usize) -> &Nibble {
+        match self.get(index) {
+            Some(nibble) => &NIBBLES[nibble.value() as usize],
+            None => panic!("Nibble index {} is out of range for length {}", index, self.len),
+        }
+    }
+}
+
+impl PartialEq for NibbleVec {
+    fn eq(&self, other: &NibbleVec) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for NibbleVec {}
+
+impl<'a> PartialEq<NibbleSlice<'a>> for NibbleVec {
+    fn eq(&self, other: &NibbleSlice<'a>) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl<'a> PartialEq<NibbleVec> for NibbleSlice<'a> {
+    fn eq(&self, other: &NibbleVec) -> bool {
+        *self == other.as_slice()
+    }
+}
+
+impl PartialOrd for NibbleVec {
+    fn partial_cmp(&self, other: &NibbleVec) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NibbleVec {
+    fn cmp(&self, other: &NibbleVec) -> Ordering {
+        self.as_slice().cmp(&other.as_slice())
+    }
+}
+
+impl Hash for NibbleVec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl fmt::Debug for NibbleVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("NibbleVec(")?;
+        for nibble in self.iter() {
+            write!(f, "{:x}", nibble)?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Extend<Nibble> for NibbleVec {
+    fn extend<I: IntoIterator<Item = Nibble>>(&mut self, nibbles: I) {
+        for nibble in nibbles {
+            self.push(nibble);
+        }
+    }
+}
+
+impl FromIterator<Nibble> for NibbleVec {
+    fn from_iter<I: IntoIterator<Item = Nibble>>(nibbles: I) -> NibbleVec {
+        let mut vec = NibbleVec::new();
+        vec.extend(nibbles);
+        vec
+    }
+}
+
+impl<'a> IntoIterator for &'a NibbleVec {
+    type Item = Nibble;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn nibbles(values: &[u8]) -> NibbleVec {
+        values.iter().map(|&value| Nibble::new(value).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_slice_from_bytes() {
+        let slice = NibbleSlice::new(&[0xAB, 0xCD]);
+
+        assert_eq!(4, slice.len());
+        assert_eq!(Nibble::new(0xA), slice.get(0));
+        assert_eq!(Nibble::new(0xD), slice.get(3));
+        assert_eq!(None, slice.get(4));
+        assert_eq!(Nibble::new(0xC).unwrap(), slice[2]);
+        assert!(NibbleSlice::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_slice_odd_offsets() {
+        let bytes = [0x12, 0x34, 0x56];
+        let slice = NibbleSlice::new(&bytes);
+
+        assert_eq!(nibbles(&[2, 3, 4]), slice.slice(1..4));
+        assert_eq!(nibbles(&[3, 4, 5, 6]), slice.slice(2..));
+        assert_eq!(nibbles(&[1, 2, 3]), slice.slice(..=2));
+        assert_eq!(nibbles(&[4, 5]), slice.slice(1..).slice(2..4));
+        assert!(slice.slice(3..3).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_slice_out_of_range() {
+        NibbleSlice::new(&[0x12]).slice(1..3);
+    }
+
+    #[test]
+    fn test_common_prefix_len() {
+        let bytes = [0x12, 0x34, 0x56];
+        let slice = NibbleSlice::new(&bytes);
+        let other = nibbles(&[2, 3, 4, 9]);
+
+        assert_eq!(0, slice.common_prefix_len(&other.as_slice()));
+        assert_eq!(3, slice.slice(1..).common_prefix_len(&other.as_slice()));
+        assert_eq!(3, other.common_prefix_len(&slice.slice(1..)));
+        assert_eq!(6, slice.common_prefix_len(&slice));
+        assert!(slice.slice(1..).starts_with(&slice.slice(1..3)));
+        assert!(!slice.slice(1..3).starts_with(&slice.slice(1..)));
+    }
+
+    #[test]
+    fn test_slice_iteration() {
+        let slice = NibbleSlice::new(&[0xAB, 0xCD]).slice(1..);
+
+        assert_eq!(vec![0xB, 0xC, 0xD], slice.iter().map(Nibble::value).collect::<Vec<_>>());
+        assert_eq!(vec![0xD, 0xC, 0xB], slice.iter().rev().map(Nibble::value).collect::<Vec<_>>());
+        assert_eq!(3, slice.iter().len());
+    }
+
+    #[test]
+    fn test_slice_comparison() {
+        let bytes = [0x12, 0x31, 0x23];
+        let slice = NibbleSlice::new(&bytes);
+
+        assert_eq!(slice.slice(0..3), slice.slice(3..6));
+        assert!(slice.slice(0..2) < slice.slice(0..3));
+        assert!(slice.slice(1..3) > slice.slice(0..3));
+        assert_eq!("NibbleSlice(123)", format!("{:?}", slice.slice(3..)));
+    }
+
+    #[test]
+    fn test_nibble_vec_push_pop() {
+        let mut vec = NibbleVec::new();
+        vec.push(Nibble::new(0xA).unwrap());
+        vec.push(Nibble::new(0xB).unwrap());
+        vec.push(Nibble::new(0xC).unwrap());
+
+        assert_eq!(3, vec.len());
+        assert_eq!(&[0xAB, 0xC0], vec.as_bytes());
+        assert_eq!(Nibble::new(0xC), vec.pop());
+        assert_eq!(&[0xAB], vec.as_bytes());
+        assert_eq!(Nibble::new(0xB), vec.pop());
+        assert_eq!(Nibble::new(0xA), vec.pop());
+        assert_eq!(None, vec.pop());
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn test_nibble_vec_set_truncate() {
+        let mut vec = NibbleVec::from_bytes(&[0x12, 0x34]);
+        vec.set(1, Nibble::new(0xF).unwrap());
+        vec.set(2, Nibble::new(0xE).unwrap());
+
+        assert_eq!(&[0x1F, 0xE4], vec.as_bytes());
+
+        vec.truncate(3);
+        assert_eq!(&[0x1F, 0xE0], vec.as_bytes());
+        assert_eq!(nibbles(&[1, 0xF, 0xE]), vec);
+
+        vec.clear();
+        assert!(vec.is_empty());
+        assert!(vec.into_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_nibble_vec_extend_from_slice() {
+        let mut vec = nibbles(&[1]);
+        vec.extend_from_slice(&NibbleSlice::new(&[0x23, 0x45]).slice(1..3));
+
+        assert_eq!(nibbles(&[1, 3, 4]), vec);
+        assert_eq!(nibbles(&[3, 4]), vec.slice(1..).to_vec());
+        assert_eq!(Nibble::new(4).unwrap(), vec[2]);
+    }
+
+    #[test]
+    fn test_nibble_vec_ordering() {
+        assert!(nibbles(&[1]) < nibbles(&[1, 0]));
+        assert!(nibbles(&[1, 2]) > nibbles(&[1, 0, 5]));
+        assert_eq!(nibbles(&[0xA, 0xB]), NibbleVec::from_bytes(&[0xAB]));
+        assert_eq!("NibbleVec(ab)", format!("{:?}", NibbleVec::from_bytes(&[0xAB])));
+    }
+
+}
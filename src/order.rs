@@ -0,0 +1,247 @@
+//! Numbering bits from either end
+//!
+//! `has_x_bit` and friends always count from the least signifigant bit, which is `Lsb0`. Network
+//! specs and many datasheets count from the most signifigant bit instead, which is `Msb0`. Views
+//! made with `ordered` and `ordered_mut` translate positions so the rest of the API, including the
+//! iterators, follows the chosen order.
+//!
+//! Positions are translated within a unit. For integers and Nibbles the unit is the whole value,
+//! while for slices, arrays and Vecs it is one element, so `Msb0` bit 0 of a byte slice is the most
+//! signifigant bit of the first byte, just like on the wire.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use {BitInformation, BitIteration, BitManipulation};
+
+/// A convention for numbering the bits inside a unit
+pub trait BitOrder {
+
+    /// Translates position X in this order to the matching least-signifigant-first position
+    fn translate(position: usize, unit: usize) -> usize;
+
+}
+
+/// Bit 0 is the least signifigant bit, which is what `has_x_bit` uses
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Lsb0;
+
+/// Bit 0 is the most signifigant bit, as in most network specs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Msb0;
+
+impl BitOrder for Lsb0 {
+    fn translate(position: usize, _unit: usize) -> usize {
+        position
+    }
+}
+
+impl BitOrder for Msb0 {
+    fn translate(position: usize, unit: usize) -> usize {
+        if unit == 0 {
+            return position;
+        }
+
+        let offset = position % unit;
+        position - offset + (unit - 1 - offset)
+    }
+}
+
+/// Access to bits in a chosen order
+pub trait BitOrdering: BitInformation {
+
+    /// Gets the number of bits that an order numbers within
+    fn ordering_unit(&self) -> usize {
+        self.number_of_bits()
+    }
+
+    /// Checks to see if bit X, numbered in order O, is set
+    fn has_ordered_bit<O: BitOrder>(&self, position: usize) -> bool {
+        self.is_bit_in_bounds(position) && self.has_x_bit(O::translate(position, self.ordering_unit()))
+    }
+
+    /// Gets a view of this value that numbers its bits in order O
+    fn ordered<O: BitOrder>(&self) -> Ordered<'_, O, Self> {
+        Ordered { value: self, order: PhantomData }
+    }
+
+    /// Gets a mutable view of this value that numbers its bits in order O
+    fn ordered_mut<O: BitOrder>(&mut self) -> OrderedMut<'_, O, Self> where Self: BitManipulation {
+        OrderedMut { value: self, order: PhantomData }
+    }
+
+}
+
+/// A view of a value with its bits numbered in order O
+pub struct Ordered<'a, O, T: ?Sized + 'a> {
+    value: &'a T,
+    order: PhantomData<O>,
+}
+
+impl<'a, O, T: ?Sized + 'a> Clone for Ordered<'a, O, T> {
+    fn clone(&self) -> Ordered<'a, O, T> {
+        *self
+    }
+}
+
+impl<'a, O, T: ?Sized + 'a> Copy for Ordered<'a, O, T> {}
+
+impl<'a, O: BitOrder, T: BitOrdering + ?Sized> BitInformation for Ordered<'a, O, T> {
+
+    /// Gets the number of bits in the viewed value
+    fn number_of_bits(&self) -> usize {
+        self.value.number_of_bits()
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.value.has_ordered_bit::<O>(position)
+    }
+
+}
+
+impl<'a, O: BitOrder, T: BitOrdering + ?Sized> BitIteration for Ordered<'a, O, T> {}
+
+impl<'a, O: BitOrder, T: BitOrdering + ?Sized> fmt::Debug for Ordered<'a, O, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in self.bits() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+/// A mutable view of a value with its bits numbered in order O
+pub struct OrderedMut<'a, O, T: ?Sized + 'a> {
+    value: &'a mut T,
+    order: PhantomData<O>,
+}
+
+impl<'a, O: BitOrder, T: BitOrdering + ?Sized> OrderedMut<'a, O, T> {
+
+    /// Translates position X to the position used by the viewed value
+    fn translate(&self, position: usize) -> usize {
+        O::translate(position, self.value.ordering_unit())
+    }
+
+}
+
+impl<'a, O: BitOrder, T: BitOrdering + ?Sized> BitInformation for OrderedMut<'a, O, T> {
+
+    /// Gets the number of bits in the viewed value
+    fn number_of_bits(&self) -> usize {
+        self.value.number_of_bits()
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.value.has_ordered_bit::<O>(position)
+    }
+
+}
+
+impl<'a, O: BitOrder, T: BitOrdering + BitManipulation + ?Sized> BitManipulation for OrderedMut<'a, O, T> {
+
+    fn set_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            let position = self.translate(position);
+            self.value.set_x_bit(position);
+        }
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        if self.is_bit_in_bounds(position) {
+            let position = self.translate(position);
+            self.value.clear_x_bit(position);
+        }
+    }
+
+}
+
+impl<'a, O: BitOrder, T: BitOrdering + ?Sized> BitIteration for OrderedMut<'a, O, T> {}
+
+macro_rules! impl_bit_ordering {
+    ($($t:ty),*) => {
+        $(
+            impl BitOrdering for $t {}
+        )*
+    }
+}
+
+impl_bit_ordering!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(3, Lsb0::translate(3, 8));
+        assert_eq!(7, Msb0::translate(0, 8));
+        assert_eq!(0, Msb0::translate(7, 8));
+        assert_eq!(15, Msb0::translate(8, 8));
+        assert_eq!(8, Msb0::translate(15, 8));
+        assert_eq!(31, Msb0::translate(0, 32));
+        assert_eq!(5, Msb0::translate(5, 0));
+    }
+
+    #[test]
+    fn test_has_ordered_bit_u8() {
+        let num = 0b10000010u8;
+
+        assert!(num.has_ordered_bit::<Msb0>(0));
+        assert!(num.has_ordered_bit::<Msb0>(6));
+        assert!(!num.has_ordered_bit::<Msb0>(1));
+        assert!(num.has_ordered_bit::<Lsb0>(1));
+        assert!(num.has_ordered_bit::<Lsb0>(7));
+        assert!(!num.has_ordered_bit::<Msb0>(8));
+    }
+
+    #[test]
+    fn test_has_ordered_bit_i32() {
+        assert!(i32::MIN.has_ordered_bit::<Msb0>(0));
+        assert!(1i32.has_ordered_bit::<Msb0>(31));
+        assert!(!1i32.has_ordered_bit::<Msb0>(0));
+    }
+
+    #[test]
+    fn test_ordered_iterators() {
+        let num = 0b00000111u8;
+
+        assert_eq!(vec![5, 6, 7], num.ordered::<Msb0>().ones().collect::<Vec<_>>());
+        assert_eq!(vec![0, 1, 2], num.ordered::<Lsb0>().ones().collect::<Vec<_>>());
+        assert_eq!(vec![(0, 5, false), (5, 3, true)], num.ordered::<Msb0>().runs().collect::<Vec<_>>());
+        assert_eq!(Some(5), num.ordered::<Msb0>().next_set_bit(0));
+        assert_eq!("00000111", format!("{:?}", num.ordered::<Msb0>()));
+        assert_eq!("11100000", format!("{:?}", num.ordered::<Lsb0>()));
+    }
+
+    #[test]
+    fn test_ordered_mut() {
+        let mut num = 0u16;
+
+        num.ordered_mut::<Msb0>().set_x_bit(0);
+        assert_eq!(0x8000, num);
+        num.ordered_mut::<Msb0>().toggle_x_bit(15);
+        assert_eq!(0x8001, num);
+        num.ordered_mut::<Msb0>().clear_x_bit(0);
+        num.ordered_mut::<Msb0>().set_x_bit(16);
+        assert_eq!(0x0001, num);
+        assert!(num.ordered_mut::<Msb0>().try_set_x_bit(16).is_err());
+
+        num.ordered_mut::<Lsb0>().set_x_bit(4);
+        assert_eq!(0x0011, num);
+    }
+
+    #[test]
+    fn test_generic_over_order() {
+        fn first_set<O: BitOrder>(value: u8) -> Option<usize> {
+            value.ordered::<O>().ones().next()
+        }
+
+        assert_eq!(Some(1), first_set::<Lsb0>(0b01000010));
+        assert_eq!(Some(1), first_set::<Msb0>(0b01000010));
+        assert_eq!(Some(3), first_set::<Msb0>(0b00010010));
+    }
+
+}
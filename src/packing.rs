@@ -0,0 +1,183 @@
+//! Packing Nibbles into bytes and integers
+//!
+//! Nibbles are always packed against the least signifigant end of the integer, so packing fewer
+//! Nibbles than the integer can hold leaves the upper Nibbles as zero. This means that packing
+//! `[1, 2, 3]` high-Nibble-first gives `0x123`, which is how BCD and hex digits are written.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use {BitWidth, Nibble};
+
+/// The order Nibbles are packed and unpacked in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NibbleOrder {
+
+    /// The first Nibble is the most signifigant, like written hex digits
+    HighFirst,
+
+    /// The first Nibble is the least signifigant
+    LowFirst,
+
+}
+
+/// Packs Nibbles into an integer and unpacks them again
+pub trait NibblePacking: BitWidth + Sized {
+
+    /// The number of Nibbles this type can hold
+    const NIBBLES: usize;
+
+    /// Packs a sequence of Nibbles, returning None if there are more than `NIBBLES` of them
+    fn pack_nibbles(nibbles: &[Nibble], order: NibbleOrder) -> Option<Self>;
+
+    /// Gets Nibble X, counting from the least signifigant Nibble
+    fn nibble_at(&self, index: usize) -> Option<Nibble>;
+
+    /// Unpacks every Nibble in this integer
+    #[cfg(feature = "alloc")]
+    fn unpack_nibbles(&self, order: NibbleOrder) -> Vec<Nibble> {
+        let nibbles = (0..Self::NIBBLES).filter_map(|index| self.nibble_at(index));
+
+        match order {
+            NibbleOrder::HighFirst => nibbles.rev().collect(),
+            NibbleOrder::LowFirst => nibbles.collect(),
+        }
+    }
+
+}
+
+macro_rules! impl_nibble_packing {
+    ($($t:ty),*) => {
+        $(
+            impl NibblePacking for $t {
+
+                const NIBBLES: usize = <$t as BitWidth>::BITS / 4;
+
+                fn pack_nibbles(nibbles: &[Nibble], order: NibbleOrder) -> Option<$t> {
+                    if nibbles.len() > Self::NIBBLES {
+                        return None;
+                    }
+
+                    let mut packed: $t = 0;
+                    let mut push = |nibble: &Nibble| packed = (packed << 4) | <$t>::from(nibble.value());
+
+                    match order {
+                        NibbleOrder::HighFirst => nibbles.iter().for_each(&mut push),
+                        NibbleOrder::LowFirst => nibbles.iter().rev().for_each(&mut push),
+                    }
+
+                    Some(packed)
+                }
+
+                fn nibble_at(&self, index: usize) -> Option<Nibble> {
+                    if index < Self::NIBBLES {
+                        Some(Nibble::from_low_bits((*self >> (index * 4)) as u8))
+                    } else {
+                        None
+                    }
+                }
+
+            }
+        )*
+    }
+}
+
+impl_nibble_packing!(u8, u16, u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn nibbles(values: &[u8]) -> Vec<Nibble> {
+        values.iter().map(|&value| Nibble::new(value).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_number_of_nibbles() {
+        assert_eq!(2, u8::NIBBLES);
+        assert_eq!(4, u16::NIBBLES);
+        assert_eq!(8, u32::NIBBLES);
+        assert_eq!(16, u64::NIBBLES);
+        assert_eq!(32, u128::NIBBLES);
+    }
+
+    #[test]
+    fn test_pack_nibbles_u8() {
+        assert_eq!(Some(0xAB), u8::pack_nibbles(&nibbles(&[0xA, 0xB]), NibbleOrder::HighFirst));
+        assert_eq!(Some(0xBA), u8::pack_nibbles(&nibbles(&[0xA, 0xB]), NibbleOrder::LowFirst));
+        assert_eq!(Some(0x0C), u8::pack_nibbles(&nibbles(&[0xC]), NibbleOrder::HighFirst));
+        assert_eq!(Some(0x00), u8::pack_nibbles(&[], NibbleOrder::LowFirst));
+        assert_eq!(None, u8::pack_nibbles(&nibbles(&[1, 2, 3]), NibbleOrder::HighFirst));
+    }
+
+    #[test]
+    fn test_pack_nibbles_bcd_u16() {
+        let digits = nibbles(&[1, 9, 8, 4]);
+
+        assert_eq!(Some(0x1984), u16::pack_nibbles(&digits, NibbleOrder::HighFirst));
+        assert_eq!(Some(0x4891), u16::pack_nibbles(&digits, NibbleOrder::LowFirst));
+        assert_eq!(Some(0x0198), u16::pack_nibbles(&digits[..3], NibbleOrder::HighFirst));
+    }
+
+    #[test]
+    fn test_pack_nibbles_u32() {
+        let values = nibbles(&[0xD, 0xE, 0xA, 0xD, 0xB, 0xE, 0xE, 0xF]);
+
+        assert_eq!(Some(0xDEADBEEF), u32::pack_nibbles(&values, NibbleOrder::HighFirst));
+        assert_eq!(Some(0xFEEBDAED), u32::pack_nibbles(&values, NibbleOrder::LowFirst));
+    }
+
+    #[test]
+    fn test_pack_nibbles_u64() {
+        let values = nibbles(&[0xF, 0xE, 0xD, 0xC, 0xB, 0xA, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+
+        assert_eq!(Some(0xFEDCBA9876543210), u64::pack_nibbles(&values, NibbleOrder::HighFirst));
+        assert_eq!(Some(0x0123456789ABCDEF), u64::pack_nibbles(&values, NibbleOrder::LowFirst));
+
+        let mut too_many = values.clone();
+        too_many.push(Nibble::MIN);
+        assert_eq!(None, u64::pack_nibbles(&too_many, NibbleOrder::HighFirst));
+    }
+
+    #[test]
+    fn test_pack_nibbles_u128() {
+        let values: Vec<Nibble> = (0..32).map(|value| Nibble::from_low_bits(value as u8)).collect();
+
+        assert_eq!(Some(0x0123456789ABCDEF0123456789ABCDEFu128), u128::pack_nibbles(&values, NibbleOrder::HighFirst));
+        assert_eq!(Some(0xFEDCBA9876543210FEDCBA9876543210u128), u128::pack_nibbles(&values, NibbleOrder::LowFirst));
+    }
+
+    #[test]
+    fn test_nibble_at() {
+        assert_eq!(Nibble::new(0xF), 0xDEADBEEFu32.nibble_at(0));
+        assert_eq!(Nibble::new(0xD), 0xDEADBEEFu32.nibble_at(7));
+        assert_eq!(None, 0xDEADBEEFu32.nibble_at(8));
+        assert_eq!(Nibble::new(0xA), 0xABu8.nibble_at(1));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_unpack_nibbles() {
+        assert_eq!(nibbles(&[0xA, 0xB]), 0xABu8.unpack_nibbles(NibbleOrder::HighFirst));
+        assert_eq!(nibbles(&[0xB, 0xA]), 0xABu8.unpack_nibbles(NibbleOrder::LowFirst));
+        assert_eq!(nibbles(&[0, 1, 2, 3]), 0x0123u16.unpack_nibbles(NibbleOrder::HighFirst));
+        assert_eq!(nibbles(&[3, 2, 1, 0]), 0x0123u16.unpack_nibbles(NibbleOrder::LowFirst));
+
+        let values: Vec<Nibble> = (0..32).map(|value| Nibble::from_low_bits(value as u8)).collect();
+        assert_eq!(values, 0x0123456789ABCDEF0123456789ABCDEFu128.unpack_nibbles(NibbleOrder::HighFirst));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_round_trip_nibbles() {
+        for &order in &[NibbleOrder::HighFirst, NibbleOrder::LowFirst] {
+            assert_eq!(Some(0x5Au8), u8::pack_nibbles(&0x5Au8.unpack_nibbles(order), order));
+            assert_eq!(Some(0xC0DEu16), u16::pack_nibbles(&0xC0DEu16.unpack_nibbles(order), order));
+            assert_eq!(Some(0x8BADF00Du32), u32::pack_nibbles(&0x8BADF00Du32.unpack_nibbles(order), order));
+            assert_eq!(Some(0x0123456789ABCDEFu64), u64::pack_nibbles(&0x0123456789ABCDEFu64.unpack_nibbles(order), order));
+        }
+    }
+
+}
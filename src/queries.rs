@@ -0,0 +1,204 @@
+//! Finding the highest and lowest set bits, and power of two helpers
+//!
+//! Bit positions are found from the raw bits, so a negative signed value has its most signifigant
+//! bit set. The power of two and logarithm helpers work on the numeric value instead, so they treat
+//! zero and negative values as having no logarithm and never being a power of two. For unsigned
+//! integers, they agree with the inherent `is_power_of_two` and `checked_next_power_of_two`.
+
+use core::convert::TryFrom;
+
+use {BitInformation, BitWidth};
+
+/// Queries that sit alongside `has_x_bit` in allocator and hashing code
+pub trait BitQueries: BitInformation + Sized {
+
+    /// Gets the position of the most signifigant set bit, or None if no bits are set
+    fn highest_set_bit(&self) -> Option<usize>;
+
+    /// Gets the position of the least signifigant set bit, or None if no bits are set
+    fn lowest_set_bit(&self) -> Option<usize>;
+
+    /// Gets the number of bits needed to hold this value, ignoring leading zeros
+    fn bit_length(&self) -> usize {
+        self.highest_set_bit().map_or(0, |position| position + 1)
+    }
+
+    /// Checks to see if this value is a positive power of two
+    fn is_power_of_two(&self) -> bool;
+
+    /// Gets the smallest power of two that is at least this value, or None if it would overflow
+    fn checked_next_power_of_two(&self) -> Option<Self>;
+
+    /// Gets the base 2 logarithm rounded down, or None if this value is not positive
+    fn floor_log2(&self) -> Option<usize>;
+
+    /// Gets the base 2 logarithm rounded up, or None if this value is not positive
+    fn ceil_log2(&self) -> Option<usize>;
+
+    /// Gets a copy of this value with only the least signifigant set bit kept
+    fn isolate_lowest_set(&self) -> Self;
+
+    /// Gets a copy of this value with the least signifigant set bit cleared
+    fn clear_lowest_set(&self) -> Self;
+
+}
+
+macro_rules! impl_bit_queries {
+    ($($t:ty => $unsigned:ty),*) => {
+        $(
+            impl BitQueries for $t {
+
+                fn highest_set_bit(&self) -> Option<usize> {
+                    match *self as $unsigned {
+                        0 => None,
+                        raw => Some(<$t as BitWidth>::BITS - 1 - raw.leading_zeros() as usize),
+                    }
+                }
+
+                fn lowest_set_bit(&self) -> Option<usize> {
+                    match *self as $unsigned {
+                        0 => None,
+                        raw => Some(raw.trailing_zeros() as usize),
+                    }
+                }
+
+                fn is_power_of_two(&self) -> bool {
+                    *self > 0 && (*self as $unsigned).is_power_of_two()
+                }
+
+                fn checked_next_power_of_two(&self) -> Option<$t> {
+                    if *self <= 1 {
+                        return Some(1);
+                    }
+
+                    let power = (*self as $unsigned).checked_next_power_of_two()?;
+                    <$t>::try_from(power).ok()
+                }
+
+                fn floor_log2(&self) -> Option<usize> {
+                    if *self > 0 {
+                        self.highest_set_bit()
+                    } else {
+                        None
+                    }
+                }
+
+                fn ceil_log2(&self) -> Option<usize> {
+                    if *self > 1 {
+                        (*self - 1).floor_log2().map(|log| log + 1)
+                    } else if *self == 1 {
+                        Some(0)
+                    } else {
+                        None
+                    }
+                }
+
+                fn isolate_lowest_set(&self) -> $t {
+                    *self & self.wrapping_neg()
+                }
+
+                fn clear_lowest_set(&self) -> $t {
+                    *self & self.wrapping_sub(1)
+                }
+
+            }
+        )*
+    }
+}
+
+impl_bit_queries!(u8 => u8, i8 => u8, u16 => u16, i16 => u16, u32 => u32, i32 => u32, u64 => u64,
+                  i64 => u64, u128 => u128, i128 => u128, usize => usize, isize => usize);
+
+#[allow(overflowing_literals)] //Just for tests, don't do this in real life, kids!
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_highest_lowest_set_bit_u8() {
+        assert_eq!(Some(5), 0b00101100u8.highest_set_bit());
+        assert_eq!(Some(2), 0b00101100u8.lowest_set_bit());
+        assert_eq!(Some(7), 0xFFu8.highest_set_bit());
+        assert_eq!(None, 0u8.highest_set_bit());
+        assert_eq!(None, 0u8.lowest_set_bit());
+    }
+
+    #[test]
+    fn test_highest_lowest_set_bit_signed() {
+        assert_eq!(Some(7), (-1i8).highest_set_bit());
+        assert_eq!(Some(0), (-1i8).lowest_set_bit());
+        assert_eq!(Some(31), i32::MIN.highest_set_bit());
+        assert_eq!(Some(31), i32::MIN.lowest_set_bit());
+        assert_eq!(Some(127), (-4i128).highest_set_bit());
+        assert_eq!(Some(2), (-4i128).lowest_set_bit());
+    }
+
+    #[test]
+    fn test_bit_length() {
+        assert_eq!(0, 0u16.bit_length());
+        assert_eq!(1, 1u16.bit_length());
+        assert_eq!(10, 1000u16.bit_length());
+        assert_eq!(64, u64::MAX.bit_length());
+        assert_eq!(64, (-1i64).bit_length());
+        assert_eq!(63, i64::MAX.bit_length());
+    }
+
+    #[test]
+    fn test_is_power_of_two() {
+        assert!(1u32.is_power_of_two());
+        assert!(0x80000000u32.is_power_of_two());
+        assert!(!0u32.is_power_of_two());
+        assert!(!6u32.is_power_of_two());
+        assert!(64i8.is_power_of_two());
+        assert!(!i8::MIN.is_power_of_two());
+        assert!(!(-2i8).is_power_of_two());
+        assert!((1usize << 20).is_power_of_two());
+        assert!((1isize << 20).is_power_of_two());
+    }
+
+    #[test]
+    fn test_checked_next_power_of_two() {
+        assert_eq!(Some(1u8), 0u8.checked_next_power_of_two());
+        assert_eq!(Some(1u8), 1u8.checked_next_power_of_two());
+        assert_eq!(Some(64u8), 33u8.checked_next_power_of_two());
+        assert_eq!(Some(128u8), 128u8.checked_next_power_of_two());
+        assert_eq!(None, 129u8.checked_next_power_of_two());
+        assert_eq!(Some(64i8), 64i8.checked_next_power_of_two());
+        assert_eq!(None, 65i8.checked_next_power_of_two());
+        assert_eq!(Some(1i8), (-5i8).checked_next_power_of_two());
+        assert_eq!(Some(1u128 << 100), ((1u128 << 100) - 7).checked_next_power_of_two());
+        assert_eq!(None, (i128::MAX).checked_next_power_of_two());
+    }
+
+    #[test]
+    fn test_log2() {
+        assert_eq!(None, 0u32.floor_log2());
+        assert_eq!(None, 0u32.ceil_log2());
+        assert_eq!(Some(0), 1u32.floor_log2());
+        assert_eq!(Some(0), 1u32.ceil_log2());
+        assert_eq!(Some(2), 5u32.floor_log2());
+        assert_eq!(Some(3), 5u32.ceil_log2());
+        assert_eq!(Some(3), 8u32.floor_log2());
+        assert_eq!(Some(3), 8u32.ceil_log2());
+        assert_eq!(None, (-8i16).floor_log2());
+        assert_eq!(None, (-8i16).ceil_log2());
+        assert_eq!(Some(14), i16::MAX.floor_log2());
+        assert_eq!(Some(15), i16::MAX.ceil_log2());
+        assert_eq!(Some(64), u64::MAX.ceil_log2());
+    }
+
+    #[test]
+    fn test_isolate_and_clear_lowest_set() {
+        assert_eq!(0b00001000u8, 0b01101000u8.isolate_lowest_set());
+        assert_eq!(0b01100000u8, 0b01101000u8.clear_lowest_set());
+        assert_eq!(0u8, 0u8.isolate_lowest_set());
+        assert_eq!(0u8, 0u8.clear_lowest_set());
+        assert_eq!(i8::MIN, i8::MIN.isolate_lowest_set());
+        assert_eq!(0i8, i8::MIN.clear_lowest_set());
+        assert_eq!(2i64, (-2i64).isolate_lowest_set());
+        assert_eq!(-4i64, (-2i64).clear_lowest_set());
+        assert_eq!(1u128 << 90, ((1u128 << 127) | (1u128 << 90)).isolate_lowest_set());
+    }
+
+}
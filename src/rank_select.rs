@@ -0,0 +1,386 @@
+//! An immutable bit vector that counts and finds set bits quickly
+//!
+//! Alongside the bits, a `RankSelect` keeps the number of set bits before every 4096-bit
+//! superblock as a u64, and the number of set bits before every word inside its superblock as a
+//! u16. That is about 26% more space than the bits themselves, and makes `rank1` and `rank0` a
+//! couple of lookups and a popcount.
+//!
+//! For `select1` and `select0` it also keeps which superblock holds every 4096th set bit and every
+//! 4096th clear bit. Selecting binary searches the superblocks between the two samples around the
+//! wanted bit, then the 64 word counts of that superblock. Those samples are at most 4096 bits of
+//! the right kind apart, so when set and clear bits are both reasonably common the window is one or
+//! two superblocks and a select takes a fixed number of steps. Where one kind of bit is very rare
+//! the window can cover many superblocks, and the search is logarithmic in how far apart the rare
+//! bits are.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use words::WORD_BITS;
+use {BitInformation, BitIteration, BitVec};
+
+/// The number of words in each superblock
+const SUPERBLOCK_WORDS: usize = 64;
+
+/// The number of bits in each superblock
+const SUPERBLOCK_BITS: usize = WORD_BITS * SUPERBLOCK_WORDS;
+
+/// The number of set or clear bits between each select sample
+const SELECT_SAMPLE: usize = 4096;
+
+/// Finds the first index below `len` that `predicate` is false for, where `predicate` is true for
+/// every index before that and false for every index after
+fn partition_point<F: Fn(usize) -> bool>(len: usize, predicate: F) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if predicate(middle) {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Finds the superblock holding every `SELECT_SAMPLE`th bit, given the number of those bits before
+/// each superblock
+fn select_samples<F: Fn(usize) -> usize>(count: usize, superblocks: usize, before: F) -> Vec<usize> {
+    let mut samples = Vec::with_capacity(count.div_ceil(SELECT_SAMPLE));
+    let mut superblock = 0;
+
+    for target in (0..count).step_by(SELECT_SAMPLE) {
+        while superblock + 1 < superblocks && before(superblock + 1) <= target {
+            superblock += 1;
+        }
+        samples.push(superblock);
+    }
+
+    samples
+}
+
+/// An immutable bit vector with rank and select support
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RankSelect {
+    words: Vec<u64>,
+    len: usize,
+    superblocks: Vec<u64>,
+    blocks: Vec<u16>,
+    ones: usize,
+    select1_samples: Vec<usize>,
+    select0_samples: Vec<usize>,
+}
+
+impl RankSelect {
+
+    /// Creates a RankSelect holding every bit of some words
+    pub fn from_words(words: &[u64]) -> RankSelect {
+        RankSelect::build(words.to_vec(), words.len() * WORD_BITS)
+    }
+
+    /// Builds the rank counts over words whose bits past `len` are all zero
+    fn build(words: Vec<u64>, len: usize) -> RankSelect {
+        let mut superblocks = Vec::with_capacity(words.len().div_ceil(SUPERBLOCK_WORDS));
+        let mut blocks = Vec::with_capacity(words.len());
+        let mut ones = 0;
+        let mut ones_in_superblock = 0;
+
+        for (index, word) in words.iter().enumerate() {
+            if index % SUPERBLOCK_WORDS == 0 {
+                superblocks.push(ones as u64);
+                ones_in_superblock = 0;
+            }
+
+            blocks.push(ones_in_superblock as u16);
+            ones_in_superblock += word.count_ones() as usize;
+            ones += word.count_ones() as usize;
+        }
+
+        let select1_samples = select_samples(ones, superblocks.len(), |index| superblocks[index] as usize);
+        let select0_samples = select_samples(len - ones, superblocks.len(), |index| index * SUPERBLOCK_BITS - superblocks[index] as usize);
+
+        RankSelect { words, len, superblocks, blocks, ones, select1_samples, select0_samples }
+    }
+
+    /// Gets the words backing this RankSelect
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Gets the number of bits in this RankSelect
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if this RankSelect has no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets bit X, or None if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(self.words.has_x_bit(index))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Gets the number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.ones
+    }
+
+    /// Gets the number of set bits before position X, panicking if X is past the length
+    pub fn rank1(&self, position: usize) -> usize {
+        assert!(position <= self.len, "rank position {} is out of range for length {}", position, self.len);
+
+        let word = position / WORD_BITS;
+        if word == self.words.len() {
+            return self.ones;
+        }
+
+        let offset = position % WORD_BITS;
+        let before = if offset == 0 { 0 } else { (self.words[word] << (WORD_BITS - offset)).count_ones() as usize };
+
+        self.superblocks[word / SUPERBLOCK_WORDS] as usize + self.blocks[word] as usize + before
+    }
+
+    /// Gets the number of clear bits before position X, panicking if X is past the length
+    pub fn rank0(&self, position: usize) -> usize {
+        position - self.rank1(position)
+    }
+
+    /// Gets the position of set bit K, counting from zero, or None if there are not that many
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        if rank >= self.ones {
+            return None;
+        }
+
+        let ones_before_superblock = |index: usize| self.superblocks[index] as usize;
+        let superblock = self.find_superblock(&self.select1_samples, rank, ones_before_superblock);
+        let remaining = rank - ones_before_superblock(superblock);
+
+        let first = superblock * SUPERBLOCK_WORDS;
+        let last = (first + SUPERBLOCK_WORDS).min(self.words.len());
+        let word = first + partition_point(last - first, |index| self.blocks[first + index] as usize <= remaining) - 1;
+        let remaining = remaining - self.blocks[word] as usize;
+
+        self.words[word].ones().nth(remaining).map(|offset| word * WORD_BITS + offset)
+    }
+
+    /// Gets the position of clear bit K, counting from zero, or None if there are not that many
+    pub fn select0(&self, rank: usize) -> Option<usize> {
+        if rank >= self.count_zeros() {
+            return None;
+        }
+
+        let zeros_before_superblock = |index: usize| index * SUPERBLOCK_BITS - self.superblocks[index] as usize;
+        let superblock = self.find_superblock(&self.select0_samples, rank, zeros_before_superblock);
+        let remaining = rank - zeros_before_superblock(superblock);
+
+        let first = superblock * SUPERBLOCK_WORDS;
+        let last = (first + SUPERBLOCK_WORDS).min(self.words.len());
+        let zeros_before_word = |index: usize| (index - first) * WORD_BITS - self.blocks[index] as usize;
+        let word = first + partition_point(last - first, |index| zeros_before_word(first + index) <= remaining) - 1;
+        let remaining = remaining - zeros_before_word(word);
+
+        self.words[word].zeros().nth(remaining).map(|offset| word * WORD_BITS + offset)
+    }
+
+    /// Finds the last superblock with at most `rank` bits of some kind before it, searching only
+    /// between the select samples on either side of that rank
+    fn find_superblock<F: Fn(usize) -> usize>(&self, samples: &[usize], rank: usize, before: F) -> usize {
+        let sample = rank / SELECT_SAMPLE;
+        let first = samples[sample];
+        let last = samples.get(sample + 1).map_or(self.superblocks.len(), |&next| next + 1);
+
+        first + partition_point(last - first, |index| before(first + index) <= rank) - 1
+    }
+
+}
+
+impl From<BitVec> for RankSelect {
+    fn from(bits: BitVec) -> RankSelect {
+        RankSelect::from(&bits)
+    }
+}
+
+impl<'a> From<&'a BitVec> for RankSelect {
+    fn from(bits: &'a BitVec) -> RankSelect {
+        RankSelect::build(bits.as_words().to_vec(), bits.len())
+    }
+}
+
+impl<'a> From<&'a [u64]> for RankSelect {
+    fn from(words: &'a [u64]) -> RankSelect {
+        RankSelect::from_words(words)
+    }
+}
+
+impl BitInformation for RankSelect {
+
+    /// Gets the number of bits contained in this RankSelect
+    fn number_of_bits(&self) -> usize {
+        self.len
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self.get(position).unwrap_or(false)
+    }
+
+}
+
+impl BitIteration for RankSelect {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self.words.next_clear_bit(position).filter(|&found| found < self.len)
+    }
+
+}
+
+impl fmt::Debug for RankSelect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RankSelect[")?;
+        for bit in self.bits() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Makes a RankSelect whose bit X is set when `pattern(X)` is true
+    fn rank_select<F: Fn(usize) -> bool>(len: usize, pattern: F) -> RankSelect {
+        (0..len).map(pattern).collect::<BitVec>().into()
+    }
+
+    #[test]
+    fn test_rank_small() {
+        let bits: RankSelect = "10110".chars().map(|bit| bit == '1').collect::<BitVec>().into();
+
+        assert_eq!(5, bits.len());
+        assert_eq!(3, bits.count_ones());
+        assert_eq!(vec![0, 1, 1, 2, 3, 3], (0..6).map(|position| bits.rank1(position)).collect::<Vec<_>>());
+        assert_eq!(vec![0, 0, 1, 1, 1, 2], (0..6).map(|position| bits.rank0(position)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_select_small() {
+        let bits: RankSelect = "10110".chars().map(|bit| bit == '1').collect::<BitVec>().into();
+
+        assert_eq!(Some(0), bits.select1(0));
+        assert_eq!(Some(2), bits.select1(1));
+        assert_eq!(Some(3), bits.select1(2));
+        assert_eq!(None, bits.select1(3));
+        assert_eq!(Some(1), bits.select0(0));
+        assert_eq!(Some(4), bits.select0(1));
+        assert_eq!(None, bits.select0(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rank_out_of_range() {
+        RankSelect::from_words(&[0]).rank1(65);
+    }
+
+    #[test]
+    fn test_rank_select_across_superblocks() {
+        let pattern = |position: usize| position.is_multiple_of(3) || position.is_multiple_of(7);
+        let bits = rank_select(20_000, pattern);
+
+        let mut ones = 0;
+        for position in 0..20_000 {
+            assert_eq!(ones, bits.rank1(position));
+            assert_eq!(position - ones, bits.rank0(position));
+            if pattern(position) {
+                assert_eq!(Some(position), bits.select1(ones));
+                ones += 1;
+            } else {
+                assert_eq!(Some(position), bits.select0(position - ones));
+            }
+        }
+
+        assert_eq!(ones, bits.rank1(20_000));
+        assert_eq!(None, bits.select1(ones));
+        assert_eq!(None, bits.select0(20_000 - ones));
+    }
+
+    #[test]
+    fn test_select_between_samples() {
+        // Dense, then a long clear stretch, then sparse again, so the samples are spread unevenly
+        let pattern = |position: usize| position < 10_000 || (position >= 150_000 && position.is_multiple_of(997));
+        let bits = rank_select(200_000, pattern);
+
+        let (mut ones, mut zeros) = (0, 0);
+        for position in 0..200_000 {
+            if pattern(position) {
+                assert_eq!(Some(position), bits.select1(ones));
+                ones += 1;
+            } else {
+                assert_eq!(Some(position), bits.select0(zeros));
+                zeros += 1;
+            }
+        }
+
+        assert_eq!(None, bits.select1(ones));
+        assert_eq!(None, bits.select0(zeros));
+    }
+
+    #[test]
+    fn test_rank_select_sparse_and_dense() {
+        let sparse = rank_select(10_000, |position| position == 9_999);
+        assert_eq!(Some(9_999), sparse.select1(0));
+        assert_eq!(Some(9_998), sparse.select0(9_998));
+        assert_eq!(1, sparse.rank1(10_000));
+
+        let dense = RankSelect::from_words(&[u64::MAX; 100]);
+        assert_eq!(Some(6_399), dense.select1(6_399));
+        assert_eq!(None, dense.select0(0));
+        assert_eq!(4_096, dense.rank1(4_096));
+    }
+
+    #[test]
+    fn test_select0_ignores_unused_bits() {
+        let bits = rank_select(70, |position| position < 69);
+
+        assert_eq!(Some(69), bits.select0(0));
+        assert_eq!(None, bits.select0(1));
+    }
+
+    #[test]
+    fn test_empty() {
+        let bits = RankSelect::from_words(&[]);
+
+        assert!(bits.is_empty());
+        assert_eq!(0, bits.rank1(0));
+        assert_eq!(None, bits.select1(0));
+        assert_eq!(None, bits.select0(0));
+    }
+
+    #[test]
+    fn test_bit_traits() {
+        let bits = RankSelect::from(&[0b1010u64, 1][..]);
+
+        assert_eq!(128, bits.number_of_bits());
+        assert!(bits.has_x_bit(64));
+        assert!(!bits.has_x_bit(128));
+        assert_eq!(vec![1, 3, 64], bits.ones().collect::<Vec<_>>());
+        assert_eq!(Some(true), bits.get(3));
+        assert_eq!("RankSelect[0101]", format!("{:?}", RankSelect::from(&"0101".chars().map(|bit| bit == '1').collect::<BitVec>())));
+    }
+
+}
@@ -0,0 +1,1289 @@
+//! Compressed bitmaps of u32 and u64 values in the Roaring format
+//!
+//! A `RoaringBitmap` splits every value into a 16-bit key and a 16-bit low part. Values sharing a
+//! key live in one container, which is a sorted array of low parts when there are at most 4096 of
+//! them, and a 65536-bit bitmap otherwise. `run_optimize` turns containers into runs of
+//! consecutive values wherever that is smaller.
+//!
+//! `serialize` and `deserialize` follow the portable Roaring format specification, so bitmaps can
+//! be exchanged with the other Roaring implementations. `RoaringTreemap` holds u64 values as a
+//! RoaringBitmap per 32-bit key, and uses the portable 64-bit format.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{BitAnd, BitOr, BitXor, Sub};
+use core::slice;
+
+use {BitError, BitInformation, BitIteration, BitManipulation, BitQueries, Ones};
+
+/// The most values an array container holds before it becomes a bitmap
+const ARRAY_LIMIT: usize = 4096;
+
+/// The number of u64 words in a bitmap container
+const BITMAP_WORDS: usize = 1024;
+
+/// The cookie of a serialized bitmap with no run containers
+const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
+
+/// The cookie of a serialized bitmap with at least one run container
+const SERIAL_COOKIE: u16 = 12347;
+
+/// Bitmaps with run containers and fewer containers than this leave out the offset header
+const NO_OFFSET_THRESHOLD: usize = 4;
+
+/// The ways two sets can be combined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl Operation {
+
+    /// Combines two words of a bitmap
+    fn words(self, ours: u64, theirs: u64) -> u64 {
+        match self {
+            Operation::Union => ours | theirs,
+            Operation::Intersection => ours & theirs,
+            Operation::Difference => ours & !theirs,
+            Operation::SymmetricDifference => ours ^ theirs,
+        }
+    }
+
+    /// Checks to see if values found in only one set or in both are kept
+    fn keeps(self, only_ours: bool, only_theirs: bool) -> bool {
+        match self {
+            Operation::Union => true,
+            Operation::Intersection => !only_ours && !only_theirs,
+            Operation::Difference => only_ours,
+            Operation::SymmetricDifference => only_ours || only_theirs,
+        }
+    }
+
+}
+
+/// The values of a RoaringBitmap that share a key
+#[derive(Clone, Debug)]
+enum Container {
+
+    /// The low parts in ascending order
+    Array(Vec<u16>),
+
+    /// One bit per low part
+    Bitmap {
+        words: Vec<u64>,
+        len: usize,
+    },
+
+    /// Runs of consecutive low parts as (start, length - 1), in ascending order
+    Run(Vec<(u16, u16)>),
+
+}
+
+impl Container {
+
+    /// Creates the smaller of an array or bitmap container from the words of a bitmap
+    fn from_words(words: Vec<u64>) -> Container {
+        let len = words.iter().map(|word| word.count_ones() as usize).sum();
+
+        if len <= ARRAY_LIMIT {
+            Container::Array(words[..].ones().map(|value| value as u16).collect())
+        } else {
+            Container::Bitmap { words, len }
+        }
+    }
+
+    /// Creates the smaller of an array or bitmap container from low parts in ascending order
+    fn from_sorted(values: Vec<u16>) -> Container {
+        if values.len() <= ARRAY_LIMIT {
+            Container::Array(values)
+        } else {
+            let mut words = vec![0; BITMAP_WORDS];
+            for &value in &values {
+                words.set_x_bit(value as usize);
+            }
+            Container::Bitmap { words, len: values.len() }
+        }
+    }
+
+    /// Gets the number of values
+    fn len(&self) -> usize {
+        match *self {
+            Container::Array(ref values) => values.len(),
+            Container::Bitmap { len, .. } => len,
+            Container::Run(ref runs) => runs.iter().map(|&(_, length)| length as usize + 1).sum(),
+        }
+    }
+
+    /// Checks to see if a low part is in this container
+    fn contains(&self, value: u16) -> bool {
+        match *self {
+            Container::Array(ref values) => values.binary_search(&value).is_ok(),
+            Container::Bitmap { ref words, .. } => words.has_x_bit(value as usize),
+            Container::Run(ref runs) => match runs.binary_search_by_key(&value, |&(start, _)| start) {
+                Ok(_) => true,
+                Err(0) => false,
+                Err(index) => {
+                    let (start, length) = runs[index - 1];
+                    value as u32 <= start as u32 + length as u32
+                },
+            },
+        }
+    }
+
+    /// Adds a low part, returning true if it was not already there
+    fn insert(&mut self, value: u16) -> bool {
+        self.unpack_runs();
+
+        let full_array = match *self {
+            Container::Array(ref mut values) => match values.binary_search(&value) {
+                Ok(_) => return false,
+                Err(index) if values.len() < ARRAY_LIMIT => {
+                    values.insert(index, value);
+                    return true;
+                },
+                Err(_) => true,
+            },
+            _ => false,
+        };
+        if full_array {
+            *self = Container::Bitmap { words: self.to_words(), len: ARRAY_LIMIT };
+        }
+
+        match *self {
+            Container::Bitmap { ref mut words, ref mut len } if !words.has_x_bit(value as usize) => {
+                words.set_x_bit(value as usize);
+                *len += 1;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Removes a low part, returning true if it was there
+    fn remove(&mut self, value: u16) -> bool {
+        self.unpack_runs();
+
+        let shrink = match *self {
+            Container::Array(ref mut values) => match values.binary_search(&value) {
+                Ok(index) => {
+                    values.remove(index);
+                    return true;
+                },
+                Err(_) => return false,
+            },
+            Container::Bitmap { ref mut words, ref mut len } if words.has_x_bit(value as usize) => {
+                words.clear_x_bit(value as usize);
+                *len -= 1;
+                *len <= ARRAY_LIMIT
+            },
+            _ => return false,
+        };
+        if shrink {
+            *self = Container::from_words(self.to_words());
+        }
+
+        true
+    }
+
+    /// Turns a run container back into an array or bitmap container
+    fn unpack_runs(&mut self) {
+        if let Container::Run(_) = *self {
+            *self = Container::from_words(self.to_words());
+        }
+    }
+
+    /// Gets the values as the words of a bitmap
+    fn to_words(&self) -> Vec<u64> {
+        match *self {
+            Container::Array(ref values) => {
+                let mut words = vec![0; BITMAP_WORDS];
+                for &value in values {
+                    words.set_x_bit(value as usize);
+                }
+                words
+            },
+            Container::Bitmap { ref words, .. } => words.clone(),
+            Container::Run(ref runs) => {
+                let mut words = vec![0; BITMAP_WORDS];
+                for &(start, length) in runs {
+                    for value in start as usize..=start as usize + length as usize {
+                        words.set_x_bit(value);
+                    }
+                }
+                words
+            },
+        }
+    }
+
+    /// Gets the values as runs of (start, length - 1)
+    fn to_runs(&self) -> Vec<(u16, u16)> {
+        let mut runs: Vec<(u16, u16)> = Vec::new();
+        for value in self.iter() {
+            match runs.last_mut() {
+                Some(&mut (start, ref mut length)) if start as u32 + *length as u32 + 1 == value as u32 => *length += 1,
+                _ => runs.push((value, 0)),
+            }
+        }
+        runs
+    }
+
+    /// Gets the smallest low part
+    fn min(&self) -> Option<u16> {
+        match *self {
+            Container::Array(ref values) => values.first().cloned(),
+            Container::Bitmap { ref words, .. } => words.next_set_bit(0).map(|value| value as u16),
+            Container::Run(ref runs) => runs.first().map(|&(start, _)| start),
+        }
+    }
+
+    /// Gets the largest low part
+    fn max(&self) -> Option<u16> {
+        match *self {
+            Container::Array(ref values) => values.last().cloned(),
+            Container::Bitmap { ref words, .. } => {
+                let (index, word) = words.iter().enumerate().rev().find(|&(_, &word)| word != 0)?;
+                word.highest_set_bit().map(|position| (index * 64 + position) as u16)
+            },
+            Container::Run(ref runs) => runs.last().map(|&(start, length)| start + length),
+        }
+    }
+
+    /// Iterates over the low parts in ascending order
+    fn iter(&self) -> ContainerIter<'_> {
+        match *self {
+            Container::Array(ref values) => ContainerIter::Array(values.iter()),
+            Container::Bitmap { ref words, .. } => ContainerIter::Bitmap(words[..].ones()),
+            Container::Run(ref runs) => ContainerIter::Run { runs: runs.iter(), current: None },
+        }
+    }
+
+    /// Combines this container with another, returning None if the result is empty
+    fn combine(&self, other: &Container, operation: Operation) -> Option<Container> {
+        let result = match (self, other) {
+            (Container::Array(ours), Container::Array(theirs)) => {
+                Container::from_sorted(merge(ours, theirs, operation))
+            },
+            _ => {
+                let mut words = self.to_words();
+                for (word, theirs) in words.iter_mut().zip(other.to_words()) {
+                    *word = operation.words(*word, theirs);
+                }
+                Container::from_words(words)
+            },
+        };
+
+        if result.len() == 0 { None } else { Some(result) }
+    }
+
+    /// Gets the number of bytes this container takes up when serialized
+    fn serialized_size(&self) -> usize {
+        match *self {
+            Container::Array(ref values) => values.len() * 2,
+            Container::Bitmap { .. } => BITMAP_WORDS * 8,
+            Container::Run(ref runs) => 2 + runs.len() * 4,
+        }
+    }
+
+}
+
+/// Merges two ascending sequences of low parts, keeping the values that an operation keeps
+fn merge(ours: &[u16], theirs: &[u16], operation: Operation) -> Vec<u16> {
+    let mut result = Vec::with_capacity(ours.len() + theirs.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < ours.len() || j < theirs.len() {
+        let (value, only_ours, only_theirs) = if j == theirs.len() || (i < ours.len() && ours[i] < theirs[j]) {
+            i += 1;
+            (ours[i - 1], true, false)
+        } else if i == ours.len() || theirs[j] < ours[i] {
+            j += 1;
+            (theirs[j - 1], false, true)
+        } else {
+            i += 1;
+            j += 1;
+            (ours[i - 1], false, false)
+        };
+
+        if operation.keeps(only_ours, only_theirs) {
+            result.push(value);
+        }
+    }
+
+    result
+}
+
+/// An iterator over the low parts of a container
+#[derive(Clone, Debug)]
+enum ContainerIter<'a> {
+    Array(slice::Iter<'a, u16>),
+    Bitmap(Ones<'a, [u64]>),
+    Run {
+        runs: slice::Iter<'a, (u16, u16)>,
+        current: Option<(u32, u32)>,
+    },
+}
+
+impl<'a> Iterator for ContainerIter<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        match *self {
+            ContainerIter::Array(ref mut values) => values.next().cloned(),
+            ContainerIter::Bitmap(ref mut ones) => ones.next().map(|value| value as u16),
+            ContainerIter::Run { ref mut runs, ref mut current } => {
+                if current.is_none() {
+                    *current = runs.next().map(|&(start, length)| (start as u32, start as u32 + length as u32));
+                }
+
+                let (value, last) = (*current)?;
+                *current = if value < last { Some((value + 1, last)) } else { None };
+                Some(value as u16)
+            },
+        }
+    }
+}
+
+/// Reads little-endian integers from serialized data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+
+    /// Takes the next X bytes
+    fn take(&mut self, count: usize) -> Result<&'a [u8], BitError> {
+        if self.bytes.len() - self.position < count {
+            return Err(BitError::MalformedData { offset: self.position });
+        }
+
+        self.position += count;
+        Ok(&self.bytes[self.position - count..self.position])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, BitError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, BitError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, BitError> {
+        let bytes = self.take(8)?;
+        let mut array = [0; 8];
+        array.copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(array))
+    }
+
+    /// Gets an error for the data just read
+    fn malformed(&self) -> BitError {
+        BitError::MalformedData { offset: self.position }
+    }
+
+}
+
+/// A compressed bitmap of u32 values
+#[derive(Clone, Default)]
+pub struct RoaringBitmap {
+    keys: Vec<u16>,
+    containers: Vec<Container>,
+}
+
+impl RoaringBitmap {
+
+    /// Creates an empty RoaringBitmap
+    pub fn new() -> RoaringBitmap {
+        RoaringBitmap { keys: Vec::new(), containers: Vec::new() }
+    }
+
+    /// Gets the number of values
+    pub fn len(&self) -> u64 {
+        self.containers.iter().map(|container| container.len() as u64).sum()
+    }
+
+    /// Checks to see if there are no values
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    /// Checks to see if X is one of the values
+    pub fn contains(&self, value: u32) -> bool {
+        match self.keys.binary_search(&((value >> 16) as u16)) {
+            Ok(index) => self.containers[index].contains(value as u16),
+            Err(_) => false,
+        }
+    }
+
+    /// Adds X, returning true if it was not already there
+    pub fn insert(&mut self, value: u32) -> bool {
+        let key = (value >> 16) as u16;
+        match self.keys.binary_search(&key) {
+            Ok(index) => self.containers[index].insert(value as u16),
+            Err(index) => {
+                self.keys.insert(index, key);
+                self.containers.insert(index, Container::Array(vec![value as u16]));
+                true
+            },
+        }
+    }
+
+    /// Removes X, returning true if it was there
+    pub fn remove(&mut self, value: u32) -> bool {
+        let index = match self.keys.binary_search(&((value >> 16) as u16)) {
+            Ok(index) => index,
+            Err(_) => return false,
+        };
+
+        let removed = self.containers[index].remove(value as u16);
+        if self.containers[index].len() == 0 {
+            self.keys.remove(index);
+            self.containers.remove(index);
+        }
+        removed
+    }
+
+    /// Removes every value
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.containers.clear();
+    }
+
+    /// Gets the smallest value, or None if there are none
+    pub fn min(&self) -> Option<u32> {
+        let low = self.containers.first()?.min()?;
+        Some((self.keys[0] as u32) << 16 | low as u32)
+    }
+
+    /// Gets the largest value, or None if there are none
+    pub fn max(&self) -> Option<u32> {
+        let low = self.containers.last()?.max()?;
+        Some((self.keys[self.keys.len() - 1] as u32) << 16 | low as u32)
+    }
+
+    /// Iterates over every value in ascending order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { keys: self.keys.iter(), containers: self.containers.iter(), current: None }
+    }
+
+    /// Gets a new RoaringBitmap holding the values of either bitmap
+    pub fn union(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, Operation::Union)
+    }
+
+    /// Gets a new RoaringBitmap holding the values of both bitmaps
+    pub fn intersection(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, Operation::Intersection)
+    }
+
+    /// Gets a new RoaringBitmap holding the values of this bitmap that are not in the other
+    pub fn difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, Operation::Difference)
+    }
+
+    /// Gets a new RoaringBitmap holding the values of exactly one of the bitmaps
+    pub fn symmetric_difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, Operation::SymmetricDifference)
+    }
+
+    /// Adds every value of the other bitmap to this one
+    pub fn union_with(&mut self, other: &RoaringBitmap) {
+        *self = self.union(other);
+    }
+
+    /// Removes every value that is not also in the other bitmap
+    pub fn intersect_with(&mut self, other: &RoaringBitmap) {
+        *self = self.intersection(other);
+    }
+
+    /// Removes every value of the other bitmap from this one
+    pub fn difference_with(&mut self, other: &RoaringBitmap) {
+        *self = self.difference(other);
+    }
+
+    /// Keeps the values that are in exactly one of the bitmaps
+    pub fn symmetric_difference_with(&mut self, other: &RoaringBitmap) {
+        *self = self.symmetric_difference(other);
+    }
+
+    /// Checks to see if every value of this bitmap is in the other
+    pub fn is_subset(&self, other: &RoaringBitmap) -> bool {
+        self.iter().all(|value| other.contains(value))
+    }
+
+    /// Checks to see if the bitmaps have no values in common
+    pub fn is_disjoint(&self, other: &RoaringBitmap) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    /// Turns containers into runs wherever that makes them smaller, returning true if any changed
+    pub fn run_optimize(&mut self) -> bool {
+        let mut changed = false;
+
+        for container in self.containers.iter_mut() {
+            let runs = container.to_runs();
+            let run_size = 2 + runs.len() * 4;
+            let plain_size = (container.len() * 2).min(BITMAP_WORDS * 8);
+
+            let optimized = match *container {
+                Container::Run(_) if run_size >= plain_size => Container::from_words(container.to_words()),
+                Container::Run(_) => continue,
+                _ if run_size < plain_size => Container::Run(runs),
+                _ => continue,
+            };
+
+            *container = optimized;
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Serializes this bitmap in the portable Roaring format
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_into(&mut bytes);
+        bytes
+    }
+
+    /// Serializes this bitmap in the portable Roaring format onto the end of some bytes
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        let size = self.containers.len();
+        let has_runs = self.containers.iter().any(|container| matches!(*container, Container::Run(_)));
+        let start = bytes.len();
+
+        if has_runs {
+            bytes.extend_from_slice(&SERIAL_COOKIE.to_le_bytes());
+            bytes.extend_from_slice(&((size - 1) as u16).to_le_bytes());
+
+            let mut flags = vec![0u8; size.div_ceil(8)];
+            for (index, container) in self.containers.iter().enumerate() {
+                if let Container::Run(_) = *container {
+                    flags.set_x_bit(index);
+                }
+            }
+            bytes.extend_from_slice(&flags);
+        } else {
+            bytes.extend_from_slice(&SERIAL_COOKIE_NO_RUNCONTAINER.to_le_bytes());
+            bytes.extend_from_slice(&(size as u32).to_le_bytes());
+        }
+
+        for (&key, container) in self.keys.iter().zip(self.containers.iter()) {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bytes.extend_from_slice(&((container.len() - 1) as u16).to_le_bytes());
+        }
+
+        if !has_runs || size >= NO_OFFSET_THRESHOLD {
+            let mut offset = bytes.len() - start + size * 4;
+            for container in &self.containers {
+                bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+                offset += container.serialized_size();
+            }
+        }
+
+        for container in &self.containers {
+            match *container {
+                Container::Array(ref values) => {
+                    for value in values {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                },
+                Container::Bitmap { ref words, .. } => {
+                    for word in words {
+                        bytes.extend_from_slice(&word.to_le_bytes());
+                    }
+                },
+                Container::Run(ref runs) => {
+                    bytes.extend_from_slice(&(runs.len() as u16).to_le_bytes());
+                    for &(start, length) in runs {
+                        bytes.extend_from_slice(&start.to_le_bytes());
+                        bytes.extend_from_slice(&length.to_le_bytes());
+                    }
+                },
+            }
+        }
+    }
+
+    /// Gets the number of bytes `serialize` would produce
+    pub fn serialized_size(&self) -> usize {
+        let size = self.containers.len();
+        let has_runs = self.containers.iter().any(|container| matches!(*container, Container::Run(_)));
+
+        let header = if has_runs { 4 + size.div_ceil(8) } else { 8 };
+        let offsets = if !has_runs || size >= NO_OFFSET_THRESHOLD { size * 4 } else { 0 };
+
+        header + size * 4 + offsets + self.containers.iter().map(Container::serialized_size).sum::<usize>()
+    }
+
+    /// Reads a bitmap in the portable Roaring format, failing if the bytes are malformed
+    pub fn deserialize(bytes: &[u8]) -> Result<RoaringBitmap, BitError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let bitmap = RoaringBitmap::read(&mut reader)?;
+
+        if reader.position != bytes.len() {
+            return Err(reader.malformed());
+        }
+        Ok(bitmap)
+    }
+
+    /// Reads a bitmap in the portable Roaring format from the current position
+    fn read(reader: &mut Reader) -> Result<RoaringBitmap, BitError> {
+        let cookie = reader.read_u32()?;
+        let (size, run_flags) = if cookie & 0xFFFF == SERIAL_COOKIE as u32 {
+            let size = (cookie >> 16) as usize + 1;
+            (size, Some(reader.take(size.div_ceil(8))?))
+        } else if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+            (reader.read_u32()? as usize, None)
+        } else {
+            return Err(BitError::MalformedData { offset: 0 });
+        };
+
+        if size > 1 << 16 {
+            return Err(reader.malformed());
+        }
+
+        let mut keys = Vec::with_capacity(size);
+        let mut lens = Vec::with_capacity(size);
+        for _ in 0..size {
+            let key = reader.read_u16()?;
+            if keys.last().is_some_and(|&last| last >= key) {
+                return Err(reader.malformed());
+            }
+            keys.push(key);
+            lens.push(reader.read_u16()? as usize + 1);
+        }
+
+        if run_flags.is_none() || size >= NO_OFFSET_THRESHOLD {
+            reader.take(size * 4)?;
+        }
+
+        let mut containers = Vec::with_capacity(size);
+        for (index, &len) in lens.iter().enumerate() {
+            let is_run = run_flags.is_some_and(|flags| flags.has_x_bit(index));
+
+            let container = if is_run {
+                let count = reader.read_u16()? as usize;
+                let mut runs = Vec::with_capacity(count);
+                let mut next = 0u32;
+                for _ in 0..count {
+                    let (start, length) = (reader.read_u16()?, reader.read_u16()?);
+                    if (start as u32) < next || start as u32 + length as u32 > u16::MAX as u32 {
+                        return Err(reader.malformed());
+                    }
+                    next = start as u32 + length as u32 + 2;
+                    runs.push((start, length));
+                }
+                Container::Run(runs)
+            } else if len > ARRAY_LIMIT {
+                let mut words = Vec::with_capacity(BITMAP_WORDS);
+                for _ in 0..BITMAP_WORDS {
+                    words.push(reader.read_u64()?);
+                }
+                Container::Bitmap { words, len }
+            } else {
+                let mut values: Vec<u16> = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let value = reader.read_u16()?;
+                    if values.last().is_some_and(|&last| last >= value) {
+                        return Err(reader.malformed());
+                    }
+                    values.push(value);
+                }
+                Container::Array(values)
+            };
+
+            if container.len() != len {
+                return Err(reader.malformed());
+            }
+            if let Container::Bitmap { ref words, .. } = container {
+                if words.iter().map(|word| word.count_ones() as usize).sum::<usize>() != len {
+                    return Err(reader.malformed());
+                }
+            }
+            containers.push(container);
+        }
+
+        Ok(RoaringBitmap { keys, containers })
+    }
+
+    /// Combines this bitmap with another, container by container
+    fn combine(&self, other: &RoaringBitmap, operation: Operation) -> RoaringBitmap {
+        let mut result = RoaringBitmap::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.keys.len() || j < other.keys.len() {
+            if j == other.keys.len() || (i < self.keys.len() && self.keys[i] < other.keys[j]) {
+                if operation.keeps(true, false) {
+                    result.push(self.keys[i], self.containers[i].clone());
+                }
+                i += 1;
+            } else if i == self.keys.len() || other.keys[j] < self.keys[i] {
+                if operation.keeps(false, true) {
+                    result.push(other.keys[j], other.containers[j].clone());
+                }
+                j += 1;
+            } else {
+                if let Some(container) = self.containers[i].combine(&other.containers[j], operation) {
+                    result.push(self.keys[i], container);
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+
+        result
+    }
+
+    /// Adds a container with a key larger than every other key
+    fn push(&mut self, key: u16, container: Container) {
+        self.keys.push(key);
+        self.containers.push(container);
+    }
+
+}
+
+impl PartialEq for RoaringBitmap {
+    fn eq(&self, other: &RoaringBitmap) -> bool {
+        self.keys == other.keys && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for RoaringBitmap {}
+
+impl fmt::Debug for RoaringBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'b> BitOr<&'b RoaringBitmap> for &RoaringBitmap {
+    type Output = RoaringBitmap;
+
+    fn bitor(self, other: &'b RoaringBitmap) -> RoaringBitmap {
+        self.union(other)
+    }
+}
+
+impl<'b> BitAnd<&'b RoaringBitmap> for &RoaringBitmap {
+    type Output = RoaringBitmap;
+
+    fn bitand(self, other: &'b RoaringBitmap) -> RoaringBitmap {
+        self.intersection(other)
+    }
+}
+
+impl<'b> BitXor<&'b RoaringBitmap> for &RoaringBitmap {
+    type Output = RoaringBitmap;
+
+    fn bitxor(self, other: &'b RoaringBitmap) -> RoaringBitmap {
+        self.symmetric_difference(other)
+    }
+}
+
+impl<'b> Sub<&'b RoaringBitmap> for &RoaringBitmap {
+    type Output = RoaringBitmap;
+
+    fn sub(self, other: &'b RoaringBitmap) -> RoaringBitmap {
+        self.difference(other)
+    }
+}
+
+impl Extend<u32> for RoaringBitmap {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<u32> for RoaringBitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(values: I) -> RoaringBitmap {
+        let mut bitmap = RoaringBitmap::new();
+        bitmap.extend(values);
+        bitmap
+    }
+}
+
+impl<'a> IntoIterator for &'a RoaringBitmap {
+    type Item = u32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a RoaringBitmap in ascending order
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    keys: slice::Iter<'a, u16>,
+    containers: slice::Iter<'a, Container>,
+    current: Option<(u32, ContainerIter<'a>)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if let Some((high, ref mut values)) = self.current {
+                if let Some(low) = values.next() {
+                    return Some(high | low as u32);
+                }
+            }
+
+            let key = *self.keys.next()?;
+            let container = self.containers.next()?;
+            self.current = Some(((key as u32) << 16, container.iter()));
+        }
+    }
+}
+
+/// A compressed bitmap of u64 values, made of a RoaringBitmap for each upper 32 bits
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct RoaringTreemap {
+    maps: Vec<(u32, RoaringBitmap)>,
+}
+
+impl RoaringTreemap {
+
+    /// Creates an empty RoaringTreemap
+    pub fn new() -> RoaringTreemap {
+        RoaringTreemap { maps: Vec::new() }
+    }
+
+    /// Gets the number of values
+    pub fn len(&self) -> u64 {
+        self.maps.iter().map(|(_, bitmap)| bitmap.len()).sum()
+    }
+
+    /// Checks to see if there are no values
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Checks to see if X is one of the values
+    pub fn contains(&self, value: u64) -> bool {
+        match self.find((value >> 32) as u32) {
+            Ok(index) => self.maps[index].1.contains(value as u32),
+            Err(_) => false,
+        }
+    }
+
+    /// Adds X, returning true if it was not already there
+    pub fn insert(&mut self, value: u64) -> bool {
+        let key = (value >> 32) as u32;
+        let index = match self.find(key) {
+            Ok(index) => index,
+            Err(index) => {
+                self.maps.insert(index, (key, RoaringBitmap::new()));
+                index
+            },
+        };
+
+        self.maps[index].1.insert(value as u32)
+    }
+
+    /// Removes X, returning true if it was there
+    pub fn remove(&mut self, value: u64) -> bool {
+        let index = match self.find((value >> 32) as u32) {
+            Ok(index) => index,
+            Err(_) => return false,
+        };
+
+        let removed = self.maps[index].1.remove(value as u32);
+        if self.maps[index].1.is_empty() {
+            self.maps.remove(index);
+        }
+        removed
+    }
+
+    /// Removes every value
+    pub fn clear(&mut self) {
+        self.maps.clear();
+    }
+
+    /// Gets the smallest value, or None if there are none
+    pub fn min(&self) -> Option<u64> {
+        let &(key, ref bitmap) = self.maps.first()?;
+        bitmap.min().map(|low| (key as u64) << 32 | low as u64)
+    }
+
+    /// Gets the largest value, or None if there are none
+    pub fn max(&self) -> Option<u64> {
+        let &(key, ref bitmap) = self.maps.last()?;
+        bitmap.max().map(|low| (key as u64) << 32 | low as u64)
+    }
+
+    /// Iterates over every value in ascending order
+    pub fn iter(&self) -> TreemapIter<'_> {
+        TreemapIter { maps: self.maps.iter(), current: None }
+    }
+
+    /// Gets a new RoaringTreemap holding the values of either treemap
+    pub fn union(&self, other: &RoaringTreemap) -> RoaringTreemap {
+        self.combine(other, Operation::Union)
+    }
+
+    /// Gets a new RoaringTreemap holding the values of both treemaps
+    pub fn intersection(&self, other: &RoaringTreemap) -> RoaringTreemap {
+        self.combine(other, Operation::Intersection)
+    }
+
+    /// Gets a new RoaringTreemap holding the values of this treemap that are not in the other
+    pub fn difference(&self, other: &RoaringTreemap) -> RoaringTreemap {
+        self.combine(other, Operation::Difference)
+    }
+
+    /// Gets a new RoaringTreemap holding the values of exactly one of the treemaps
+    pub fn symmetric_difference(&self, other: &RoaringTreemap) -> RoaringTreemap {
+        self.combine(other, Operation::SymmetricDifference)
+    }
+
+    /// Turns containers into runs wherever that makes them smaller, returning true if any changed
+    pub fn run_optimize(&mut self) -> bool {
+        self.maps.iter_mut().fold(false, |changed, &mut (_, ref mut bitmap)| bitmap.run_optimize() | changed)
+    }
+
+    /// Serializes this treemap in the portable 64-bit Roaring format
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.maps.len() as u64).to_le_bytes());
+        for &(key, ref bitmap) in &self.maps {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bitmap.serialize_into(&mut bytes);
+        }
+        bytes
+    }
+
+    /// Reads a treemap in the portable 64-bit Roaring format, failing if the bytes are malformed
+    pub fn deserialize(bytes: &[u8]) -> Result<RoaringTreemap, BitError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let count = reader.read_u64()?;
+        let mut maps: Vec<(u32, RoaringBitmap)> = Vec::new();
+
+        for _ in 0..count {
+            let key = reader.read_u32()?;
+            if maps.last().is_some_and(|&(last, _)| last >= key) {
+                return Err(reader.malformed());
+            }
+
+            let bitmap = RoaringBitmap::read(&mut reader)?;
+            if !bitmap.is_empty() {
+                maps.push((key, bitmap));
+            }
+        }
+
+        if reader.position != bytes.len() {
+            return Err(reader.malformed());
+        }
+        Ok(RoaringTreemap { maps })
+    }
+
+    /// Finds the bitmap for the upper 32 bits of a value
+    fn find(&self, key: u32) -> Result<usize, usize> {
+        self.maps.binary_search_by_key(&key, |&(key, _)| key)
+    }
+
+    /// Combines this treemap with another, bitmap by bitmap
+    fn combine(&self, other: &RoaringTreemap, operation: Operation) -> RoaringTreemap {
+        let mut maps = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.maps.len() || j < other.maps.len() {
+            if j == other.maps.len() || (i < self.maps.len() && self.maps[i].0 < other.maps[j].0) {
+                if operation.keeps(true, false) {
+                    maps.push(self.maps[i].clone());
+                }
+                i += 1;
+            } else if i == self.maps.len() || other.maps[j].0 < self.maps[i].0 {
+                if operation.keeps(false, true) {
+                    maps.push(other.maps[j].clone());
+                }
+                j += 1;
+            } else {
+                let bitmap = self.maps[i].1.combine(&other.maps[j].1, operation);
+                if !bitmap.is_empty() {
+                    maps.push((self.maps[i].0, bitmap));
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+
+        RoaringTreemap { maps }
+    }
+
+}
+
+impl fmt::Debug for RoaringTreemap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Extend<u64> for RoaringTreemap {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<u64> for RoaringTreemap {
+    fn from_iter<I: IntoIterator<Item = u64>>(values: I) -> RoaringTreemap {
+        let mut treemap = RoaringTreemap::new();
+        treemap.extend(values);
+        treemap
+    }
+}
+
+impl<'a> IntoIterator for &'a RoaringTreemap {
+    type Item = u64;
+    type IntoIter = TreemapIter<'a>;
+
+    fn into_iter(self) -> TreemapIter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a RoaringTreemap in ascending order
+#[derive(Clone, Debug)]
+pub struct TreemapIter<'a> {
+    maps: slice::Iter<'a, (u32, RoaringBitmap)>,
+    current: Option<(u64, Iter<'a>)>,
+}
+
+impl<'a> Iterator for TreemapIter<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            if let Some((high, ref mut values)) = self.current {
+                if let Some(low) = values.next() {
+                    return Some(high | low as u64);
+                }
+            }
+
+            let &(key, ref bitmap) = self.maps.next()?;
+            self.current = Some(((key as u64) << 32, bitmap.iter()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn bitmap(values: &[u32]) -> RoaringBitmap {
+        values.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_insert_remove_contains() {
+        let mut values = RoaringBitmap::new();
+
+        assert!(values.insert(5));
+        assert!(values.insert(70_000));
+        assert!(values.insert(u32::MAX));
+        assert!(!values.insert(5));
+        assert_eq!(3, values.len());
+        assert!(values.contains(70_000));
+        assert!(!values.contains(70_001));
+        assert_eq!(Some(5), values.min());
+        assert_eq!(Some(u32::MAX), values.max());
+
+        assert!(values.remove(70_000));
+        assert!(!values.remove(70_000));
+        assert!(!values.remove(123_456));
+        assert_eq!(vec![5, u32::MAX], values.iter().collect::<Vec<_>>());
+
+        values.clear();
+        assert!(values.is_empty());
+        assert_eq!(None, values.min());
+    }
+
+    #[test]
+    fn test_array_to_bitmap_and_back() {
+        let mut values: RoaringBitmap = (0..10_000).map(|value| value * 2).collect();
+
+        assert_eq!(10_000, values.len());
+        assert!(matches!(values.containers[0], Container::Bitmap { .. }));
+        assert_eq!(Some(19_998), values.max());
+        assert_eq!((0..10_000).map(|value| value * 2).collect::<Vec<_>>(), values.iter().collect::<Vec<_>>());
+
+        for value in 0..6_000 {
+            values.remove(value * 2);
+        }
+        assert!(matches!(values.containers[0], Container::Array(_)));
+        assert_eq!(4_000, values.len());
+        assert_eq!(Some(12_000), values.min());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: RoaringBitmap = (0..6_000).chain(100_000..100_010).collect();
+        let b: RoaringBitmap = (3_000..9_000).chain(200_000..200_005).collect();
+
+        let expected = |keep: &dyn Fn(bool, bool) -> bool| -> Vec<u32> {
+            let mut all: Vec<u32> = a.iter().chain(b.iter()).collect();
+            all.sort();
+            all.dedup();
+            all.into_iter().filter(|&value| keep(a.contains(value), b.contains(value))).collect()
+        };
+
+        assert_eq!(expected(&|x, y| x || y), a.union(&b).iter().collect::<Vec<_>>());
+        assert_eq!(expected(&|x, y| x && y), a.intersection(&b).iter().collect::<Vec<_>>());
+        assert_eq!(expected(&|x, y| x && !y), a.difference(&b).iter().collect::<Vec<_>>());
+        assert_eq!(expected(&|x, y| x != y), a.symmetric_difference(&b).iter().collect::<Vec<_>>());
+
+        assert_eq!(a.union(&b), &a | &b);
+        assert_eq!(a.intersection(&b), &a & &b);
+        assert_eq!(a.difference(&b), &a - &b);
+        assert_eq!(a.symmetric_difference(&b), &a ^ &b);
+
+        let mut c = a.clone();
+        c.union_with(&b);
+        c.difference_with(&b);
+        c.symmetric_difference_with(&a);
+        assert_eq!(a.intersection(&b), c);
+        c.intersect_with(&bitmap(&[3_000, 9_000]));
+        assert_eq!(vec![3_000], c.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_subset_and_disjoint() {
+        let small = bitmap(&[1, 70_000]);
+        let large = bitmap(&[1, 2, 70_000, 1 << 31]);
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(small.is_disjoint(&bitmap(&[3, 70_001])));
+        assert!(!small.is_disjoint(&large));
+    }
+
+    #[test]
+    fn test_run_optimize() {
+        let mut values: RoaringBitmap = (1..=100).chain(65_536..65_536 + 10_000).chain([200_000].iter().cloned()).collect();
+        let before = values.clone();
+
+        assert!(values.run_optimize());
+        assert!(matches!(values.containers[0], Container::Run(_)));
+        assert!(matches!(values.containers[1], Container::Run(_)));
+        assert!(matches!(values.containers[2], Container::Array(_)));
+        assert_eq!(before, values);
+        assert_eq!(10_101, values.len());
+        assert!(values.contains(100));
+        assert!(!values.contains(101));
+        assert_eq!(Some(65_536 + 9_999), before.iter().filter(|&value| value < 200_000).last());
+        assert!(!values.run_optimize());
+
+        values.insert(101);
+        assert!(matches!(values.containers[0], Container::Array(_)));
+        assert!(values.contains(101));
+        values.remove(65_540);
+        assert_eq!(10_101, values.len());
+    }
+
+    #[test]
+    fn test_serialize_spec_layout() {
+        let values = bitmap(&[1, 2, 3]);
+
+        assert_eq!(vec![0x3A, 0x30, 0, 0, 1, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 1, 0, 2, 0, 3, 0], values.serialize());
+        assert_eq!(vec![0x3A, 0x30, 0, 0, 0, 0, 0, 0], RoaringBitmap::new().serialize());
+
+        let mut runs: RoaringBitmap = (1..=100).collect();
+        runs.run_optimize();
+        assert_eq!(vec![0x3B, 0x30, 0, 0, 1, 0, 0, 99, 0, 1, 0, 1, 0, 99, 0], runs.serialize());
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let mut values: RoaringBitmap = (0..5_000).map(|value| value * 3)
+                                                  .chain(1 << 20..(1 << 20) + 300)
+                                                  .chain((0..8).map(|key| key << 16 | 7))
+                                                  .collect();
+
+        for &optimize in &[false, true] {
+            if optimize {
+                values.run_optimize();
+            }
+
+            let bytes = values.serialize();
+            assert_eq!(values.serialized_size(), bytes.len());
+            assert_eq!(Ok(values.clone()), RoaringBitmap::deserialize(&bytes));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_malformed() {
+        let bytes = bitmap(&[1, 2, 3]).serialize();
+
+        assert_eq!(Err(BitError::MalformedData { offset: 20 }), RoaringBitmap::deserialize(&bytes[..21]));
+        assert_eq!(Err(BitError::MalformedData { offset: 10 }), RoaringBitmap::deserialize(&bytes[..11]));
+        assert_eq!(Err(BitError::MalformedData { offset: 0 }), RoaringBitmap::deserialize(&[1, 2, 3, 4, 0, 0, 0, 0]));
+        assert!(RoaringBitmap::deserialize(&[0x3A, 0x30, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 16, 0, 0, 0, 5, 0, 5, 0]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(RoaringBitmap::deserialize(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_treemap() {
+        let mut values = RoaringTreemap::new();
+
+        assert!(values.insert(5));
+        assert!(values.insert(1 << 40));
+        assert!(values.insert(u64::MAX));
+        assert!(!values.insert(5));
+        assert_eq!(3, values.len());
+        assert!(values.contains(1 << 40));
+        assert!(!values.contains(1 << 41));
+        assert_eq!(Some(5), values.min());
+        assert_eq!(Some(u64::MAX), values.max());
+        assert_eq!(vec![5, 1 << 40, u64::MAX], values.iter().collect::<Vec<_>>());
+
+        assert!(values.remove(1 << 40));
+        assert_eq!(vec![5, u64::MAX], (&values).into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_treemap_set_operations() {
+        let a: RoaringTreemap = [1, 2, 1 << 33, 1 << 34].iter().cloned().collect();
+        let b: RoaringTreemap = [2, 3, 1 << 34, 1 << 35].iter().cloned().collect();
+
+        assert_eq!(vec![1, 2, 3, 1 << 33, 1 << 34, 1 << 35], a.union(&b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![2, 1 << 34], a.intersection(&b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![1, 1 << 33], a.difference(&b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![1, 3, 1 << 33, 1 << 35], a.symmetric_difference(&b).iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_treemap_serialize() {
+        let mut values: RoaringTreemap = (0..1_000u64).map(|value| value << 30).chain(10..20).collect();
+        values.run_optimize();
+
+        let bytes = values.serialize();
+        assert_eq!(values.maps.len() as u64, u64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]));
+        assert_eq!(Ok(values.clone()), RoaringTreemap::deserialize(&bytes));
+        assert_eq!(vec![0; 8], RoaringTreemap::new().serialize());
+        assert!(RoaringTreemap::deserialize(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!("{1, 70000}", format!("{:?}", bitmap(&[70_000, 1])));
+        assert_eq!("{1, 4294967296}", format!("{:?}", [1u64 << 32, 1].iter().cloned().collect::<RoaringTreemap>()));
+    }
+
+}
@@ -0,0 +1,332 @@
+//! Bit information for slices, arrays and Vecs of integers
+//!
+//! Bit positions run across the elements, so bit 0 is bit 0 of the first element and bit
+//! `T::BITS` is bit 0 of the second.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use {BitInformation, BitIteration, BitManipulation, BitOrdering, BitWidth};
+
+impl<T: BitWidth> BitInformation for [T] {
+
+    /// Gets the number of bits contained in every element
+    fn number_of_bits(&self) -> usize {
+        self.len() * T::BITS
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        match self.get(position / T::BITS) {
+            Some(element) => element.has_x_bit(position % T::BITS),
+            None => false,
+        }
+    }
+
+}
+
+impl<T: BitWidth + BitManipulation> BitManipulation for [T] {
+
+    fn set_x_bit(&mut self, position: usize) {
+        if let Some(element) = self.get_mut(position / T::BITS) {
+            element.set_x_bit(position % T::BITS);
+        }
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        if let Some(element) = self.get_mut(position / T::BITS) {
+            element.clear_x_bit(position % T::BITS);
+        }
+    }
+
+    fn toggle_x_bit(&mut self, position: usize) {
+        if let Some(element) = self.get_mut(position / T::BITS) {
+            element.toggle_x_bit(position % T::BITS);
+        }
+    }
+
+}
+
+impl<T: BitWidth + BitIteration> BitIteration for [T] {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        let first = position / T::BITS;
+        let mut offset = position % T::BITS;
+
+        for (index, element) in self.iter().enumerate().skip(first) {
+            if let Some(found) = element.next_set_bit(offset) {
+                return Some(index * T::BITS + found);
+            }
+            offset = 0;
+        }
+
+        None
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        let first = position / T::BITS;
+        let mut offset = position % T::BITS;
+
+        for (index, element) in self.iter().enumerate().skip(first) {
+            if let Some(found) = element.next_clear_bit(offset) {
+                return Some(index * T::BITS + found);
+            }
+            offset = 0;
+        }
+
+        None
+    }
+
+}
+
+impl<T: BitWidth> BitOrdering for [T] {
+
+    /// Gets the number of bits in one element, so orders number the bits of each element in turn
+    fn ordering_unit(&self) -> usize {
+        T::BITS
+    }
+
+}
+
+impl<T: BitWidth, const N: usize> BitWidth for [T; N] {
+    const BITS: usize = N * T::BITS;
+}
+
+impl<T: BitWidth, const N: usize> BitInformation for [T; N] {
+
+    /// Gets the number of bits contained in every element
+    fn number_of_bits(&self) -> usize {
+        <Self as BitWidth>::BITS
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self[..].has_x_bit(position)
+    }
+
+}
+
+impl<T: BitWidth + BitManipulation, const N: usize> BitManipulation for [T; N] {
+
+    fn set_x_bit(&mut self, position: usize) {
+        self[..].set_x_bit(position);
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        self[..].clear_x_bit(position);
+    }
+
+    fn toggle_x_bit(&mut self, position: usize) {
+        self[..].toggle_x_bit(position);
+    }
+
+}
+
+impl<T: BitWidth + BitIteration, const N: usize> BitIteration for [T; N] {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self[..].next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self[..].next_clear_bit(position)
+    }
+
+}
+
+impl<T: BitWidth, const N: usize> BitOrdering for [T; N] {
+
+    /// Gets the number of bits in one element, so orders number the bits of each element in turn
+    fn ordering_unit(&self) -> usize {
+        T::BITS
+    }
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T: BitWidth> BitInformation for Vec<T> {
+
+    /// Gets the number of bits contained in every element
+    fn number_of_bits(&self) -> usize {
+        self[..].number_of_bits()
+    }
+
+    fn has_x_bit(&self, position: usize) -> bool {
+        self[..].has_x_bit(position)
+    }
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T: BitWidth + BitManipulation> BitManipulation for Vec<T> {
+
+    fn set_x_bit(&mut self, position: usize) {
+        self[..].set_x_bit(position);
+    }
+
+    fn clear_x_bit(&mut self, position: usize) {
+        self[..].clear_x_bit(position);
+    }
+
+    fn toggle_x_bit(&mut self, position: usize) {
+        self[..].toggle_x_bit(position);
+    }
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T: BitWidth + BitIteration> BitIteration for Vec<T> {
+
+    fn next_set_bit(&self, position: usize) -> Option<usize> {
+        self[..].next_set_bit(position)
+    }
+
+    fn next_clear_bit(&self, position: usize) -> Option<usize> {
+        self[..].next_clear_bit(position)
+    }
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T: BitWidth> BitOrdering for Vec<T> {
+
+    /// Gets the number of bits in one element, so orders number the bits of each element in turn
+    fn ordering_unit(&self) -> usize {
+        T::BITS
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::vec::Vec;
+    use {number_of_bits, BitError, Msb0, Nibble};
+
+    #[test]
+    fn test_number_of_bits_slices() {
+        assert_eq!(24, [0u8; 3][..].number_of_bits());
+        assert_eq!(48, [0i16; 3].number_of_bits());
+        assert_eq!(48, number_of_bits::<[i16; 3]>());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_number_of_bits_vec() {
+        assert_eq!(640, vec![0u64; 10].number_of_bits());
+        assert_eq!(0, Vec::<u32>::new().number_of_bits());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_has_x_bit_vec_u64() {
+        let mut bitmap = vec![0u64; 20];
+        bitmap[15] = 1 << 40;
+        bitmap[0] = 1;
+
+        assert!(bitmap.has_x_bit(0));
+        assert!(bitmap.has_x_bit(1000));
+        assert!(!bitmap.has_x_bit(999));
+        assert!(!bitmap.has_x_bit(1001));
+        assert!(!bitmap.has_x_bit(1280));
+        assert!(!bitmap.has_most_signifigant_bit());
+    }
+
+    #[test]
+    fn test_has_x_bit_slice_u8() {
+        let bytes = [0b00000001u8, 0b10000000u8, 0xFFu8];
+        let slice = &bytes[..];
+
+        assert!(slice.has_x_bit(0));
+        assert!(!slice.has_x_bit(8));
+        assert!(slice.has_x_bit(15));
+        assert!(slice.has_x_bit(23));
+        assert!(slice.has_most_signifigant_bit());
+        assert_eq!(Ok(true), slice.try_has_x_bit(23));
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 24, number_of_bits: 24 }), slice.try_has_x_bit(24));
+    }
+
+    #[test]
+    fn test_most_signifigant_bit_empty_slices() {
+        let empty: [u8; 0] = [];
+        assert!(!empty.has_most_signifigant_bit());
+        assert!(!empty[..].has_most_signifigant_bit());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_most_signifigant_bit_empty_vec() {
+        assert!(!Vec::<u64>::new().has_most_signifigant_bit());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_bit_manipulation_vec_u64() {
+        let mut bitmap = vec![0u64; 4];
+
+        bitmap.set_x_bit(130);
+        assert_eq!(vec![0, 0, 4, 0], bitmap);
+        bitmap.toggle_x_bit(255);
+        assert_eq!(1 << 63, bitmap[3]);
+        bitmap.assign_x_bit(130, false);
+        bitmap.clear_x_bit(255);
+        assert_eq!(vec![0u64; 4], bitmap);
+
+        bitmap.set_x_bit(256);
+        assert_eq!(vec![0u64; 4], bitmap);
+        assert_eq!(Err(BitError::PositionOutOfBounds { position: 256, number_of_bits: 256 }), bitmap.try_set_x_bit(256));
+    }
+
+    #[test]
+    fn test_bit_manipulation_array() {
+        let flags = [0u32; 2].with_x_bit(33).with_x_bit(1);
+
+        assert_eq!([2u32, 2u32], flags);
+        assert_eq!([2u32, 0u32], flags.without_x_bit(33));
+
+        let mut bytes = [0i8; 2];
+        bytes[..].set_x_bit(15);
+        assert_eq!([0i8, i8::MIN], bytes);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_bit_iteration_vec_u64() {
+        let mut bitmap = vec![0u64; 3];
+        bitmap.set_x_bit(5);
+        bitmap.set_x_bit(64);
+        bitmap.set_x_bit(191);
+
+        assert_eq!(vec![5, 64, 191], bitmap.ones().collect::<Vec<_>>());
+        assert_eq!(189, bitmap.zeros().count());
+        assert_eq!(Some(64), bitmap.next_set_bit(6));
+        assert_eq!(Some(65), bitmap.next_clear_bit(64));
+        assert_eq!(None, bitmap.next_set_bit(192));
+        assert_eq!(vec![(0, 5, false), (5, 1, true), (6, 58, false), (64, 1, true), (65, 126, false), (191, 1, true)],
+                   bitmap.runs().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bit_iteration_nibbles() {
+        let nibbles = [Nibble::MAX, Nibble::MIN, Nibble::low_of(0b0100)];
+
+        assert_eq!(vec![0, 1, 2, 3, 10], nibbles.ones().collect::<Vec<_>>());
+        assert_eq!(Some(4), nibbles.next_clear_bit(0));
+    }
+
+    #[test]
+    fn test_msb0_byte_slice() {
+        let packet = [0b10000000u8, 0b00000001u8];
+
+        assert!(packet[..].has_ordered_bit::<Msb0>(0));
+        assert!(packet.has_ordered_bit::<Msb0>(15));
+        assert!(!packet.has_ordered_bit::<Msb0>(7));
+        assert!(!packet.has_ordered_bit::<Msb0>(16));
+        assert_eq!(vec![0, 15], packet.ordered::<Msb0>().ones().collect::<Vec<_>>());
+
+        let mut header = vec![0u8; 2];
+        header.ordered_mut::<Msb0>().set_x_bit(1);
+        header.ordered_mut::<Msb0>().set_x_bit(12);
+        assert_eq!(vec![0b01000000, 0b00001000], header);
+    }
+
+}
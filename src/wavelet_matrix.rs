@@ -0,0 +1,309 @@
+//! A sequence of integers that answers rank, select and range queries over its values
+//!
+//! A wavelet matrix keeps one `RankSelect` per bit of the alphabet, starting from the most
+//! signifigant bit. Level X holds that bit of every value, after the values have been stably sorted
+//! by the bits of the levels above, with every clear bit before every set bit. Following a value
+//! down the levels with `rank0` and `rank1` then takes one step per bit, no matter how long the
+//! sequence is.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use {BitInformation, BitQueries, BitVec, RankSelect};
+
+/// A sequence of u64s with rank, select and range queries over its values
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WaveletMatrix {
+    levels: Vec<RankSelect>,
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl WaveletMatrix {
+
+    /// Builds a WaveletMatrix with just enough levels for the largest value
+    pub fn new(values: &[u64]) -> WaveletMatrix {
+        let width = values.iter().map(|value| value.bit_length()).max().unwrap_or(0);
+        let mut levels = Vec::with_capacity(width);
+        let mut zeros = Vec::with_capacity(width);
+        let mut current = values.to_vec();
+
+        for level in 0..width {
+            let bit = width - 1 - level;
+            let bits: BitVec = current.iter().map(|value| value.has_x_bit(bit)).collect();
+
+            let (mut clear, set): (Vec<u64>, Vec<u64>) = current.iter().partition(|value| !value.has_x_bit(bit));
+            zeros.push(clear.len());
+            clear.extend(set);
+            current = clear;
+
+            levels.push(RankSelect::from(bits));
+        }
+
+        WaveletMatrix { levels, zeros, len: values.len() }
+    }
+
+    /// Gets the number of values
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks to see if there are no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the number of bits in the alphabet, which is the bit length of the largest value
+    pub fn alphabet_bits(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Gets value X, or None if it is out of bounds
+    pub fn access(&self, index: usize) -> Option<u64> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut position = index;
+        let mut value = 0;
+        for (level, bits) in self.levels.iter().enumerate() {
+            value <<= 1;
+            if bits.has_x_bit(position) {
+                value |= 1;
+                position = self.zeros[level] + bits.rank1(position);
+            } else {
+                position = bits.rank0(position);
+            }
+        }
+
+        Some(value)
+    }
+
+    /// Counts the times `symbol` appears before position X, panicking if X is past the length
+    pub fn rank(&self, symbol: u64, position: usize) -> usize {
+        assert!(position <= self.len, "rank position {} is out of range for length {}", position, self.len);
+
+        if symbol.bit_length() > self.alphabet_bits() {
+            return 0;
+        }
+
+        let range = self.descend(symbol, 0..position);
+        range.end - range.start
+    }
+
+    /// Finds the position of occurrence K of `symbol`, counting from zero, or None if there are not
+    /// that many
+    pub fn select(&self, symbol: u64, rank: usize) -> Option<usize> {
+        if symbol.bit_length() > self.alphabet_bits() {
+            return None;
+        }
+
+        let range = self.descend(symbol, 0..self.len);
+        if rank >= range.end - range.start {
+            return None;
+        }
+
+        let mut position = range.start + rank;
+        for (level, bits) in self.levels.iter().enumerate().rev() {
+            position = if symbol.has_x_bit(self.alphabet_bits() - 1 - level) {
+                bits.select1(position - self.zeros[level])?
+            } else {
+                bits.select0(position)?
+            };
+        }
+
+        Some(position)
+    }
+
+    /// Gets the Kth smallest value in a range of positions, counting from zero, or None if the
+    /// range has K or fewer values
+    pub fn range_quantile(&self, range: Range<usize>, rank: usize) -> Option<u64> {
+        self.check_range(&range);
+        if range.start >= range.end || rank >= range.end - range.start {
+            return None;
+        }
+
+        let (mut start, mut end, mut rank) = (range.start, range.end, rank);
+        let mut value = 0;
+        for (level, bits) in self.levels.iter().enumerate() {
+            value <<= 1;
+            let clear = bits.rank0(end) - bits.rank0(start);
+
+            if rank < clear {
+                start = bits.rank0(start);
+                end = bits.rank0(end);
+            } else {
+                rank -= clear;
+                value |= 1;
+                start = self.zeros[level] + bits.rank1(start);
+                end = self.zeros[level] + bits.rank1(end);
+            }
+        }
+
+        Some(value)
+    }
+
+    /// Counts the values in a range of positions that are inside a range of values
+    pub fn range_frequency(&self, range: Range<usize>, values: Range<u64>) -> usize {
+        self.check_range(&range);
+        if range.start >= range.end || values.start >= values.end {
+            return 0;
+        }
+
+        self.count_less(&range, values.end) - self.count_less(&range, values.start)
+    }
+
+    /// Counts the values in a range of positions that are less than X
+    fn count_less(&self, range: &Range<usize>, value: u64) -> usize {
+        if value.bit_length() > self.alphabet_bits() {
+            return range.end - range.start;
+        }
+
+        let (mut start, mut end) = (range.start, range.end);
+        let mut count = 0;
+        for (level, bits) in self.levels.iter().enumerate() {
+            if value.has_x_bit(self.alphabet_bits() - 1 - level) {
+                count += bits.rank0(end) - bits.rank0(start);
+                start = self.zeros[level] + bits.rank1(start);
+                end = self.zeros[level] + bits.rank1(end);
+            } else {
+                start = bits.rank0(start);
+                end = bits.rank0(end);
+            }
+        }
+
+        count
+    }
+
+    /// Follows a range of positions down to the range that `symbol` sorts into on the last level
+    fn descend(&self, symbol: u64, range: Range<usize>) -> Range<usize> {
+        let (mut start, mut end) = (range.start, range.end);
+        for (level, bits) in self.levels.iter().enumerate() {
+            if symbol.has_x_bit(self.alphabet_bits() - 1 - level) {
+                start = self.zeros[level] + bits.rank1(start);
+                end = self.zeros[level] + bits.rank1(end);
+            } else {
+                start = bits.rank0(start);
+                end = bits.rank0(end);
+            }
+        }
+
+        start..end
+    }
+
+    /// Panics if a range of positions goes past the length
+    fn check_range(&self, range: &Range<usize>) {
+        assert!(range.end <= self.len, "range {}..{} is out of range for length {}", range.start, range.end, self.len);
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn text() -> Vec<u64> {
+        vec![5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]
+    }
+
+    #[test]
+    fn test_access() {
+        let values = text();
+        let matrix = WaveletMatrix::new(&values);
+
+        assert_eq!(values.len(), matrix.len());
+        assert_eq!(3, matrix.alphabet_bits());
+        for (index, &value) in values.iter().enumerate() {
+            assert_eq!(Some(value), matrix.access(index));
+        }
+        assert_eq!(None, matrix.access(values.len()));
+    }
+
+    #[test]
+    fn test_rank_and_select() {
+        let values = text();
+        let matrix = WaveletMatrix::new(&values);
+
+        for symbol in 0..9 {
+            let positions: Vec<usize> = (0..values.len()).filter(|&index| values[index] == symbol).collect();
+
+            for position in 0..=values.len() {
+                assert_eq!(positions.iter().filter(|&&found| found < position).count(), matrix.rank(symbol, position));
+            }
+            for (rank, &position) in positions.iter().enumerate() {
+                assert_eq!(Some(position), matrix.select(symbol, rank));
+            }
+            assert_eq!(None, matrix.select(symbol, positions.len()));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rank_out_of_range() {
+        WaveletMatrix::new(&text()).rank(5, 13);
+    }
+
+    #[test]
+    fn test_range_quantile() {
+        let values = text();
+        let matrix = WaveletMatrix::new(&values);
+
+        let mut sorted = values[2..9].to_vec();
+        sorted.sort();
+        for (rank, &value) in sorted.iter().enumerate() {
+            assert_eq!(Some(value), matrix.range_quantile(2..9, rank));
+        }
+        assert_eq!(None, matrix.range_quantile(2..9, 7));
+        assert_eq!(None, matrix.range_quantile(4..4, 0));
+        assert_eq!(Some(0), matrix.range_quantile(0..12, 0));
+        assert_eq!(Some(6), matrix.range_quantile(0..12, 11));
+    }
+
+    #[test]
+    fn test_range_frequency() {
+        let values = text();
+        let matrix = WaveletMatrix::new(&values);
+
+        for start in 0..values.len() {
+            for end in start..=values.len() {
+                let expected = values[start..end].iter().filter(|&&value| (2..6).contains(&value)).count();
+                assert_eq!(expected, matrix.range_frequency(start..end, 2..6));
+            }
+        }
+        assert_eq!(12, matrix.range_frequency(0..12, 0..u64::MAX));
+        assert_eq!(0, matrix.range_frequency(0..12, 7..100));
+        assert_eq!(5, matrix.range_frequency(0..12, 5..6));
+    }
+
+    #[test]
+    fn test_wide_values() {
+        let values = vec![u64::MAX, 0, 1 << 40, u64::MAX, 7];
+        let matrix = WaveletMatrix::new(&values);
+
+        assert_eq!(64, matrix.alphabet_bits());
+        assert_eq!(Some(1 << 40), matrix.access(2));
+        assert_eq!(2, matrix.rank(u64::MAX, 5));
+        assert_eq!(Some(3), matrix.select(u64::MAX, 1));
+        assert_eq!(Some(7), matrix.range_quantile(0..5, 1));
+        assert_eq!(2, matrix.range_frequency(0..5, 1..u64::MAX));
+    }
+
+    #[test]
+    fn test_empty_and_zeros() {
+        let empty = WaveletMatrix::new(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(0, empty.rank(0, 0));
+        assert_eq!(None, empty.select(0, 0));
+
+        let zeros = WaveletMatrix::new(&[0, 0, 0]);
+        assert_eq!(0, zeros.alphabet_bits());
+        assert_eq!(Some(0), zeros.access(1));
+        assert_eq!(2, zeros.rank(0, 2));
+        assert_eq!(Some(2), zeros.select(0, 2));
+        assert_eq!(0, zeros.rank(1, 3));
+        assert_eq!(Some(0), zeros.range_quantile(0..3, 2));
+        assert_eq!(3, zeros.range_frequency(0..3, 0..1));
+    }
+
+}
@@ -0,0 +1,132 @@
+//! Helpers for bits packed into u64 words
+//!
+//! Bit X lives in bit `X % 64` of word `X / 64`, which is how `BitArray` and `BitVec` store their
+//! bits.
+
+/// The number of bits in each word
+pub const WORD_BITS: usize = 64;
+
+/// Gets the number of u64 words needed to hold X bits
+pub const fn words_for_bits(bits: usize) -> usize {
+    bits.div_ceil(WORD_BITS)
+}
+
+/// Sets every bit from `start` up to (but not including) `end` to `value`
+pub fn fill(words: &mut [u64], start: usize, end: usize, value: bool) {
+    let mut position = start;
+    while position < end {
+        let offset = position % WORD_BITS;
+        let width = (WORD_BITS - offset).min(end - position);
+        let mask = (u64::MAX >> (WORD_BITS - width)) << offset;
+
+        if value {
+            words[position / WORD_BITS] |= mask;
+        } else {
+            words[position / WORD_BITS] &= !mask;
+        }
+
+        position += width;
+    }
+}
+
+/// Zeroes the bits of the last word that are past `len`
+pub fn clear_unused_bits(words: &mut [u64], len: usize) {
+    let used = len % WORD_BITS;
+    if used != 0 {
+        if let Some(last) = words.last_mut() {
+            *last &= (1u64 << used) - 1;
+        }
+    }
+}
+
+/// Moves bit X to bit X + `amount`, dropping the bits that move past the last word
+pub fn shift_up(words: &mut [u64], amount: usize) {
+    let word_shift = amount / WORD_BITS;
+    let bit_shift = amount % WORD_BITS;
+
+    for index in (0..words.len()).rev() {
+        words[index] = if index < word_shift {
+            0
+        } else {
+            let source = index - word_shift;
+            let mut word = words[source] << bit_shift;
+            if bit_shift != 0 && source > 0 {
+                word |= words[source - 1] >> (WORD_BITS - bit_shift);
+            }
+            word
+        };
+    }
+}
+
+/// Moves bit X to bit X - `amount`, dropping the bits that move below bit 0
+pub fn shift_down(words: &mut [u64], amount: usize) {
+    let word_shift = amount / WORD_BITS;
+    let bit_shift = amount % WORD_BITS;
+
+    for index in 0..words.len() {
+        let source = index.saturating_add(word_shift);
+        words[index] = if source >= words.len() {
+            0
+        } else {
+            let mut word = words[source] >> bit_shift;
+            if bit_shift != 0 && source + 1 < words.len() {
+                word |= words[source + 1] << (WORD_BITS - bit_shift);
+            }
+            word
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let mut words = [0u64; 3];
+
+        fill(&mut words, 60, 130, true);
+        assert_eq!([0xF << 60, u64::MAX, 0b11], words);
+        fill(&mut words, 62, 129, false);
+        assert_eq!([0b11 << 60, 0, 0b10], words);
+        fill(&mut words, 5, 5, true);
+        assert_eq!([0b11 << 60, 0, 0b10], words);
+    }
+
+    #[test]
+    fn test_clear_unused_bits() {
+        let mut words = [u64::MAX; 2];
+
+        clear_unused_bits(&mut words, 128);
+        assert_eq!([u64::MAX; 2], words);
+        clear_unused_bits(&mut words, 70);
+        assert_eq!([u64::MAX, 0b111111], words);
+        clear_unused_bits(&mut [], 3);
+    }
+
+    #[test]
+    fn test_shift_up() {
+        let mut words = [1 << 63 | 1, 1 << 62, 0];
+
+        shift_up(&mut words, 1);
+        assert_eq!([0b10, 1 | 1 << 63, 0], words);
+        shift_up(&mut words, 65);
+        assert_eq!([0, 0b100, 0b10], words);
+        shift_up(&mut words, 1000);
+        assert_eq!([0; 3], words);
+    }
+
+    #[test]
+    fn test_shift_down() {
+        let mut words = [0b10, 1 | 1 << 63, 0b1];
+
+        shift_down(&mut words, 1);
+        assert_eq!([1 << 63 | 1, 1 << 62 | 1 << 63, 0], words);
+        shift_down(&mut words, 126);
+        assert_eq!([0b11, 0, 0], words);
+        shift_down(&mut words, usize::MAX);
+        assert_eq!([0; 3], words);
+    }
+
+}